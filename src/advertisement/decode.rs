@@ -0,0 +1,204 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::advertisement::MiBeacon;
+use crate::mijiabt_data::Reading;
+
+/// The suffix of the 128-bit UUIDs derived from the 16-bit and 32-bit bluetooth SIG UUIDs.
+const BLUETOOTH_BASE_UUID_SUFFIX: &str = "-0000-1000-8000-00805f9b34fb";
+/// The prefix of a manufacturer identifier, when parsed from a string.
+const MANUFACTURER_PREFIX: &str = "manufacturer:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Where the bytes of an advertisement come from.
+pub enum DataSource {
+    /// The service data of a service, by 128-bit UUID in lower case, e.g. 0000fe95-0000-1000-8000-00805f9b34fb.
+    ServiceData(String),
+    /// The manufacturer specific data of a company, by company identifier.
+    ManufacturerData(u16),
+}
+
+impl DataSource {
+    /// Create a new service data source.
+    ///
+    /// # Arguments:
+    /// * `uuid` - The service UUID, either 16-bit (fe95), 32-bit or 128-bit, with or without a 0x prefix.
+    ///
+    /// Returns the source, with the UUID expanded to 128 bits, or None if the UUID is invalid.
+    pub fn service(uuid: &str) -> Option<DataSource> {
+        let uuid = uuid.trim();
+        let uuid = uuid.strip_prefix("0x").unwrap_or(uuid).to_lowercase();
+
+        let is_hex = |part: &str| part.chars().all(|c| c.is_ascii_hexdigit());
+
+        let uuid = match uuid.len() {
+            4 if is_hex(&uuid) => format!("0000{}{}", uuid, BLUETOOTH_BASE_UUID_SUFFIX),
+            8 if is_hex(&uuid) => format!("{}{}", uuid, BLUETOOTH_BASE_UUID_SUFFIX),
+            36 if uuid.split('-').map(str::len).eq([8, 4, 4, 4, 12].iter().copied()) && is_hex(&uuid.replace('-', "")) => uuid,
+            _ => return None
+        };
+
+        Some(DataSource::ServiceData(uuid))
+    }
+}
+
+impl FromStr for DataSource {
+    type Err = String;
+
+    /// Parse a data source: a service UUID, e.g. fe95, or a company identifier prefixed with manufacturer:, e.g. manufacturer:0x038f.
+    fn from_str(source: &str) -> Result<DataSource, String> {
+        match source.strip_prefix(MANUFACTURER_PREFIX) {
+            Some(company_id) => {
+                let company_id = company_id.trim();
+                let parsed = match company_id.strip_prefix("0x") {
+                    Some(hex) => u16::from_str_radix(hex, 16),
+                    None => company_id.parse::<u16>()
+                };
+
+                parsed.map(DataSource::ManufacturerData)
+                    .map_err(|_| format!("invalid company identifier \"{}\", expected e.g. 0x038f", company_id))
+            },
+            None => DataSource::service(source)
+                .ok_or_else(|| format!("invalid service UUID \"{}\", expected e.g. fe95 or 0000fe95-0000-1000-8000-00805f9b34fb", source))
+        }
+    }
+}
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataSource::ServiceData(uuid) => write!(f, "service {}", uuid),
+            DataSource::ManufacturerData(company_id) => write!(f, "manufacturer 0x{:04x}", company_id)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+/// A frame decoded from the bytes of an advertisement.
+pub enum DecodedFrame {
+    /// A MiBeacon frame, from the service data of the fe95 service.
+    MiBeacon(MiBeacon),
+}
+
+impl DecodedFrame {
+    /// Get the reading carried by the frame.
+    ///
+    /// Returns the reading, or None if the frame carries no known value.
+    pub fn reading(&self) -> Option<Reading> {
+        match self {
+            DecodedFrame::MiBeacon(mibeacon) => mibeacon.reading()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// An error while decoding the bytes of an advertisement.
+pub enum DecodeError {
+    /// No decoder is known for the source of the bytes.
+    UnsupportedSource(DataSource),
+    /// The bytes are not a valid frame.
+    InvalidFrame(DataSource),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnsupportedSource(source) => write!(f, "No decoder for {}", source),
+            DecodeError::InvalidFrame(source) => write!(f, "Invalid frame for {}", source)
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Decode the bytes of an advertisement, independently of bluez.
+///
+/// # Arguments:
+/// * `source` - Where the bytes come from: a service UUID or a company identifier.
+/// * `data` - The service data or the manufacturer specific data.
+///
+/// Returns the decoded frame, or an error if the source is not supported or the bytes are not a valid frame.
+pub fn decode(source: &DataSource, data: &[u8]) -> Result<DecodedFrame, DecodeError> {
+    match source {
+        DataSource::ServiceData(uuid) if uuid == MiBeacon::SERVICE_UUID => MiBeacon::parse(data)
+            .map(DecodedFrame::MiBeacon)
+            .ok_or_else(|| DecodeError::InvalidFrame(source.clone())),
+        _ => Err(DecodeError::UnsupportedSource(source.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The MiBeacon service UUID.
+    const FE95: &str = "0000fe95-0000-1000-8000-00805f9b34fb";
+
+    #[test]
+    fn expands_a_16_bit_uuid() {
+        assert_eq!(DataSource::service("fe95"), Some(DataSource::ServiceData(String::from(FE95))));
+        assert_eq!(DataSource::service("0xFE95"), Some(DataSource::ServiceData(String::from(FE95))));
+    }
+
+    #[test]
+    fn expands_a_32_bit_uuid() {
+        assert_eq!(DataSource::service("0000fe95"), Some(DataSource::ServiceData(String::from(FE95))));
+    }
+
+    #[test]
+    fn lowercases_a_128_bit_uuid() {
+        assert_eq!(DataSource::service("0000FE95-0000-1000-8000-00805F9B34FB"), Some(DataSource::ServiceData(String::from(FE95))));
+    }
+
+    #[test]
+    fn rejects_invalid_uuids() {
+        assert_eq!(DataSource::service("fe9"), None);
+        assert_eq!(DataSource::service("fe9g"), None);
+        assert_eq!(DataSource::service("0000fe95-0000-1000-8000-00805f9b34f"), None);
+        assert_eq!(DataSource::service("0000fe95+0000-1000-8000-00805f9b34fb"), None);
+        assert_eq!(DataSource::service("0000fe95-0000-1000-8000-00805f9b34fg"), None);
+    }
+
+    #[test]
+    fn parses_a_source() {
+        assert_eq!("fe95".parse::<DataSource>(), Ok(DataSource::ServiceData(String::from(FE95))));
+        assert_eq!("manufacturer:0x038f".parse::<DataSource>(), Ok(DataSource::ManufacturerData(0x038f)));
+        assert_eq!("manufacturer:911".parse::<DataSource>(), Ok(DataSource::ManufacturerData(911)));
+    }
+
+    #[test]
+    fn rejects_an_invalid_source() {
+        assert!("manufacturer:0xzz".parse::<DataSource>().unwrap_err().contains("invalid company identifier"));
+        assert!("manufacturer:0x10000".parse::<DataSource>().is_err());
+        assert!("fe95x".parse::<DataSource>().unwrap_err().contains("invalid service UUID"));
+    }
+
+    #[test]
+    fn decodes_a_mibeacon_frame() {
+        let source = DataSource::service("fe95").unwrap();
+        let frame = decode(&source, &[0x40, 0x20, 0xaa, 0x01, 0x01, 0x04, 0x10, 0x02, 0xd7, 0x00]).unwrap();
+
+        match &frame {
+            DecodedFrame::MiBeacon(mibeacon) => assert_eq!(mibeacon.product_id, 0x01AA)
+        }
+        assert_eq!(frame.reading().and_then(|reading| reading.temperature).map(|temperature| temperature.celsius()), Some(21.5));
+    }
+
+    #[test]
+    fn rejects_an_invalid_frame() {
+        let source = DataSource::service("fe95").unwrap();
+
+        assert_eq!(decode(&source, &[0x40, 0x20]), Err(DecodeError::InvalidFrame(source)));
+    }
+
+    #[test]
+    fn rejects_an_unsupported_source() {
+        let source = DataSource::ManufacturerData(0x038f);
+        assert_eq!(decode(&source, &[0x01, 0x02]), Err(DecodeError::UnsupportedSource(source)));
+
+        let source = DataSource::service("181a").unwrap();
+        assert_eq!(decode(&source, &[0x01, 0x02]), Err(DecodeError::UnsupportedSource(source)));
+    }
+}
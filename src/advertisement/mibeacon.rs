@@ -0,0 +1,248 @@
+use crate::mijiabt_data::{BatteryLevel, Humidity, Reading, Temperature};
+
+/// The MiBeacon frame control flags.
+const FRAME_CONTROL_ENCRYPTED: u16 = 0x0008;
+const FRAME_CONTROL_MAC_INCLUDED: u16 = 0x0010;
+const FRAME_CONTROL_CAPABILITY_INCLUDED: u16 = 0x0020;
+const FRAME_CONTROL_OBJECT_INCLUDED: u16 = 0x0040;
+
+/// The MiBeacon object types.
+const OBJECT_TEMPERATURE: u16 = 0x1004;
+const OBJECT_HUMIDITY: u16 = 0x1006;
+const OBJECT_BATTERY: u16 = 0x100A;
+const OBJECT_TEMPERATURE_HUMIDITY: u16 = 0x100D;
+
+/// The length of the frame header: frame control, product id and frame counter.
+const HEADER_LENGTH: usize = 5;
+/// The length of a MAC address.
+const MAC_LENGTH: usize = 6;
+/// The length of the object header: object type and data length.
+const OBJECT_HEADER_LENGTH: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A MiBeacon object, i.e. a typed value carried by a MiBeacon frame.
+pub struct MiBeaconObject {
+    /// The object type, e.g. 0x100D for temperature and humidity.
+    pub object_type: u16,
+    /// The length of the data announced by the frame.
+    pub length: u8,
+    /// The data. It is shorter than the announced length when the frame is truncated.
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A MiBeacon frame, as sent by Xiaomi devices in the service data of the fe95 service UUID.
+pub struct MiBeacon {
+    /// The frame control flags.
+    pub frame_control: u16,
+    /// The product identifier of the device, e.g. 0x01AA for the MJ_HT_V1.
+    pub product_id: u16,
+    /// The frame counter, incremented by the device for each new frame.
+    pub frame_counter: u8,
+    /// The MAC address of the device, if included.
+    pub address: Option<String>,
+    /// The capability of the device, if included.
+    pub capability: Option<u8>,
+    /// The object, if included and not encrypted.
+    pub object: Option<MiBeaconObject>,
+}
+
+impl MiBeacon {
+    /// The UUID of the service the MiBeacon frames are advertised for.
+    pub const SERVICE_UUID: &'static str = "0000fe95-0000-1000-8000-00805f9b34fb";
+
+    /// Parse a MiBeacon frame.
+    /// The fields which do not fit in a truncated frame are left to None.
+    ///
+    /// # Arguments:
+    /// * `data` - The service data.
+    ///
+    /// Returns the frame, or None if the data is too short to contain the frame header.
+    pub fn parse(data: &[u8]) -> Option<MiBeacon> {
+        if data.len() < HEADER_LENGTH {
+            return None;
+        }
+
+        let frame_control = u16::from_le_bytes([data[0], data[1]]);
+        let mut mibeacon = MiBeacon {
+            frame_control,
+            product_id: u16::from_le_bytes([data[2], data[3]]),
+            frame_counter: data[4],
+            address: None,
+            capability: None,
+            object: None
+        };
+
+        let mut rest = &data[HEADER_LENGTH..];
+
+        if frame_control & FRAME_CONTROL_MAC_INCLUDED != 0 {
+            if rest.len() < MAC_LENGTH {
+                return Some(mibeacon);
+            }
+
+            let address: Vec<String> = rest[..MAC_LENGTH].iter().rev().map(|byte| format!("{:02X}", byte)).collect();
+            mibeacon.address = Some(address.join(":"));
+            rest = &rest[MAC_LENGTH..];
+        }
+
+        if frame_control & FRAME_CONTROL_CAPABILITY_INCLUDED != 0 {
+            match rest.split_first() {
+                Some((capability, remaining)) => {
+                    mibeacon.capability = Some(*capability);
+                    rest = remaining;
+                },
+                None => return Some(mibeacon)
+            }
+        }
+
+        if frame_control & FRAME_CONTROL_OBJECT_INCLUDED != 0 && !mibeacon.is_encrypted() && rest.len() >= OBJECT_HEADER_LENGTH {
+            let length = rest[2];
+            let data = &rest[OBJECT_HEADER_LENGTH..];
+
+            mibeacon.object = Some(MiBeaconObject {
+                object_type: u16::from_le_bytes([rest[0], rest[1]]),
+                length,
+                data: data[..data.len().min(usize::from(length))].to_vec()
+            });
+        }
+
+        Some(mibeacon)
+    }
+
+    /// Check whether the frame is encrypted.
+    /// The object of an encrypted frame cannot be decoded without the bind key of the device.
+    pub fn is_encrypted(&self) -> bool {
+        self.frame_control & FRAME_CONTROL_ENCRYPTED != 0
+    }
+
+    /// Decode the object of the frame into a reading.
+    ///
+    /// Returns the reading, or None if the frame has no object, or an unknown or truncated one.
+    pub fn reading(&self) -> Option<Reading> {
+        let object = self.object.as_ref()?;
+        let data = &object.data;
+
+        let mut reading = Reading {
+            temperature: None,
+            humidity: None,
+            battery: None,
+            voltage: None
+        };
+
+        match (object.object_type, data.len()) {
+            (OBJECT_TEMPERATURE_HUMIDITY, 4) => {
+                reading.temperature = Some(Temperature::from_tenths(i16::from_le_bytes([data[0], data[1]])));
+                reading.humidity = Some(Humidity::from_tenths(u16::from_le_bytes([data[2], data[3]])));
+            },
+            (OBJECT_TEMPERATURE, 2) => reading.temperature = Some(Temperature::from_tenths(i16::from_le_bytes([data[0], data[1]]))),
+            (OBJECT_HUMIDITY, 2) => reading.humidity = Some(Humidity::from_tenths(u16::from_le_bytes([data[0], data[1]]))),
+            (OBJECT_BATTERY, 1) => reading.battery = Some(BatteryLevel::from_percent(data[0])),
+            _ => return None
+        }
+
+        Some(reading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_temperature_and_humidity_frame_with_mac() {
+        let mibeacon = MiBeacon::parse(&[0x50, 0x20, 0xaa, 0x01, 0xb4, 0x12, 0x8f, 0xda, 0xa8, 0x65, 0x4c, 0x0d, 0x10, 0x04, 0xd7, 0x00, 0xc4, 0x01]).unwrap();
+
+        assert_eq!(mibeacon.frame_control, 0x2050);
+        assert_eq!(mibeacon.product_id, 0x01AA);
+        assert_eq!(mibeacon.frame_counter, 0xb4);
+        assert_eq!(mibeacon.address.as_deref(), Some("4C:65:A8:DA:8F:12"));
+        assert_eq!(mibeacon.capability, None);
+        assert!(!mibeacon.is_encrypted());
+
+        let reading = mibeacon.reading().unwrap();
+        assert_eq!(reading.temperature, Some(Temperature::from_celsius(21.5)));
+        assert_eq!(reading.humidity, Some(Humidity::from_percent(45.2)));
+        assert_eq!(reading.battery, None);
+    }
+
+    #[test]
+    fn decodes_a_negative_temperature() {
+        let reading = MiBeacon::parse(&[0x40, 0x20, 0xaa, 0x01, 0x01, 0x04, 0x10, 0x02, 0xc9, 0xff]).unwrap().reading().unwrap();
+
+        assert_eq!(reading.temperature, Some(Temperature::from_celsius(-5.5)));
+        assert_eq!(reading.humidity, None);
+    }
+
+    #[test]
+    fn decodes_a_humidity_frame() {
+        let reading = MiBeacon::parse(&[0x40, 0x20, 0xaa, 0x01, 0x02, 0x06, 0x10, 0x02, 0xc4, 0x01]).unwrap().reading().unwrap();
+
+        assert_eq!(reading.temperature, None);
+        assert_eq!(reading.humidity, Some(Humidity::from_percent(45.2)));
+    }
+
+    #[test]
+    fn decodes_a_battery_frame() {
+        let reading = MiBeacon::parse(&[0x40, 0x20, 0xaa, 0x01, 0x03, 0x0a, 0x10, 0x01, 0x5d]).unwrap().reading().unwrap();
+
+        assert_eq!(reading.battery, Some(BatteryLevel::from_percent(93)));
+        assert_eq!(reading.temperature, None);
+    }
+
+    #[test]
+    fn ignores_an_unknown_object() {
+        let mibeacon = MiBeacon::parse(&[0x40, 0x20, 0xaa, 0x01, 0x04, 0x07, 0x10, 0x03, 0x01, 0x02, 0x03]).unwrap();
+
+        assert_eq!(mibeacon.object.as_ref().map(|object| object.object_type), Some(0x1007));
+        assert_eq!(mibeacon.reading(), None);
+    }
+
+    #[test]
+    fn rejects_a_truncated_header() {
+        assert_eq!(MiBeacon::parse(&[0x50, 0x20, 0xaa, 0x01]), None);
+    }
+
+    #[test]
+    fn leaves_a_truncated_mac_out() {
+        let mibeacon = MiBeacon::parse(&[0x50, 0x20, 0xaa, 0x01, 0x05, 0x12, 0x8f]).unwrap();
+
+        assert_eq!(mibeacon.frame_counter, 0x05);
+        assert_eq!(mibeacon.address, None);
+        assert_eq!(mibeacon.object, None);
+    }
+
+    #[test]
+    fn leaves_a_truncated_capability_out() {
+        let mibeacon = MiBeacon::parse(&[0x60, 0x20, 0xaa, 0x01, 0x05]).unwrap();
+
+        assert_eq!(mibeacon.capability, None);
+        assert_eq!(mibeacon.object, None);
+    }
+
+    #[test]
+    fn parses_the_capability() {
+        let mibeacon = MiBeacon::parse(&[0x60, 0x20, 0xaa, 0x01, 0x05, 0x08, 0x0a, 0x10, 0x01, 0x64]).unwrap();
+
+        assert_eq!(mibeacon.capability, Some(0x08));
+        assert_eq!(mibeacon.reading().unwrap().battery, Some(BatteryLevel::from_percent(100)));
+    }
+
+    #[test]
+    fn does_not_decode_truncated_object_data() {
+        let mibeacon = MiBeacon::parse(&[0x40, 0x20, 0xaa, 0x01, 0x05, 0x0d, 0x10, 0x04, 0xd7, 0x00]).unwrap();
+
+        let object = mibeacon.object.as_ref().unwrap();
+        assert_eq!(object.length, 4);
+        assert_eq!(object.data, vec![0xd7, 0x00]);
+        assert_eq!(mibeacon.reading(), None);
+    }
+
+    #[test]
+    fn does_not_decode_an_encrypted_object() {
+        let mibeacon = MiBeacon::parse(&[0x48, 0x20, 0xaa, 0x01, 0x05, 0x0d, 0x10, 0x04, 0xd7, 0x00, 0xc4, 0x01]).unwrap();
+
+        assert!(mibeacon.is_encrypted());
+        assert_eq!(mibeacon.object, None);
+        assert_eq!(mibeacon.reading(), None);
+    }
+}
@@ -0,0 +1,7 @@
+mod decode;
+mod mibeacon;
+mod raw_advertisement;
+
+pub use decode::{decode, DataSource, DecodeError, DecodedFrame};
+pub use mibeacon::{MiBeacon, MiBeaconObject};
+pub use raw_advertisement::Advertisement;
@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use crate::advertisement::MiBeacon;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A raw bluetooth advertisement, as received from bluez, whatever the device.
+pub struct Advertisement {
+    /// The bluez dbus path of the device, e.g. /org/bluez/hci0/dev_4C_65_A8_DA_8F_12.
+    pub path: String,
+    /// The MAC address of the device.
+    pub address: String,
+    /// The bluez alias of the device.
+    pub alias: String,
+    /// The name of the adapter the advertisement has been received on, e.g. hci0.
+    pub adapter: String,
+    /// The received signal strength, in dBm.
+    pub rssi: Option<i16>,
+    /// The service data, by service UUID.
+    pub service_data: BTreeMap<String, Vec<u8>>,
+    /// The manufacturer specific data, by company identifier.
+    pub manufacturer_data: BTreeMap<u16, Vec<u8>>,
+    /// The wall clock time the advertisement has been received at.
+    pub received_at: SystemTime,
+}
+
+impl Advertisement {
+    /// Parse the MiBeacon frame of the advertisement, if any.
+    ///
+    /// Returns the MiBeacon frame, or None if the advertisement has no valid service data for the MiBeacon service UUID.
+    pub fn mibeacon(&self) -> Option<MiBeacon> {
+        self.service_data.get(MiBeacon::SERVICE_UUID).and_then(|data| MiBeacon::parse(data))
+    }
+}
@@ -4,8 +4,29 @@ use std::collections::HashMap;
 pub struct BleDevice {
     /// The ble identifier of the device.
     pub path: String,
+    /// The MAC address of the device.
+    pub address: String,
+    /// The name of the adapter the device has been found with, e.g. hci0.
+    pub adapter: String,
+    /// The received signal strength of the last advertisement, in dBm.
+    pub rssi: Option<i16>,
     /// The local name advertised by the device.
     pub local_name: String,
     /// The service data advertised by the device.
-    pub service_data: HashMap<String, Vec<u8>>
+    pub service_data: HashMap<String, Vec<u8>>,
+    /// The manufacturer specific data advertised by the device, by company identifier.
+    pub manufacturer_data: HashMap<u16, Vec<u8>>
+}
+
+#[derive(Debug, Clone)]
+/// A high-level ble adapter representation.
+pub struct BleAdapter {
+    /// The name of the adapter, e.g. hci0.
+    pub name: String,
+    /// The MAC address of the adapter.
+    pub address: String,
+    /// The alias of the adapter.
+    pub alias: String,
+    /// Whether the adapter is powered or not.
+    pub powered: bool
 }
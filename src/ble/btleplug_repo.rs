@@ -13,6 +13,8 @@ use btleplug::api::CentralEvent::DeviceDiscovered;
 
 use btleplug::Error;
 
+use log::{error, warn};
+
 // Temporary
 use btleplug::api::{UUID, ValueNotification};
 
@@ -144,12 +146,12 @@ impl BtleplugBleRepo {
             match device.as_ref().unwrap().connect() {
                 Err(e) => {
                     match e {
-                        Error::PermissionDenied => println!("Permission denied"),
-                        Error::DeviceNotFound => println!("Device not found"),
-                        Error::NotConnected => println!("Not connected"),
-                        Error::NotSupported(d) => println!("Not supported: {}", d),
-                        Error::TimedOut(d) => println!("Timed out: {}", d.as_secs()),
-                        Error::Other(d) => println!("Other: {}", d)
+                        Error::PermissionDenied => error!("Permission denied"),
+                        Error::DeviceNotFound => error!("Device not found"),
+                        Error::NotConnected => error!("Not connected"),
+                        Error::NotSupported(d) => error!("Not supported: {}", d),
+                        Error::TimedOut(d) => error!("Timed out: {}", d.as_secs()),
+                        Error::Other(d) => error!("Other: {}", d)
                     }
                     false
                 },
@@ -188,7 +190,7 @@ impl BtleplugBleRepo {
         }
         else
         {
-            println!("No device found");
+            warn!("No device found");
         }
 
         return result_str.lock().unwrap().to_string();
@@ -10,12 +10,16 @@ use dbus::channel::Token;
 use dbus::message::{MatchRule, MessageType, Message};
 use dbus::strings::{Interface, Member};
 
+use log::warn;
+
 use crate::ble::dbus::bluez_dbus::{OrgBluezAdapter1, OrgFreedesktopDBusObjectManager};
 
-use crate::ble::api::BleDevice;
+use crate::ble::api::{BleAdapter, BleDevice};
 
 const BLUEZ_DBUS_DESTINATION: &str = "org.bluez";
 const BLUEZ_DBUS_DEVICE_INTERFACE: &str = "org.bluez.Device1";
+const BLUEZ_DBUS_ADAPTER_INTERFACE: &str = "org.bluez.Adapter1";
+const BLUEZ_DBUS_ADAPTER_PATH_PREFIX: &str = "/org/bluez/";
 const DBUS_CONNECTION_TIMEOUT_MS: u64 = 5000;
 const DBUS_CONNECTION_PROCESS_TIMEOUT_MS: u64 = 1000;
 const DBUS_CONNECTION_PROCESS_PERIOD_MS: u64 = 50;
@@ -27,6 +31,8 @@ static DBUS_CONNECTION_PROCESS_PERIOD: Duration = Duration::from_millis(DBUS_CON
 /// A ble repo using Dbus.
 /// It allows to access bluetooth using bluez dbus api.
 pub struct DbusBleRepo {
+    /// The dbus path of the adapter used to scan, e.g. /org/bluez/hci0.
+    adapter_path: String,
     /// The underlying dbus connection.
     dbus_connection: Arc<Mutex<SyncConnection>>,
     /// The list of cached found_devices.
@@ -41,10 +47,14 @@ pub struct DbusBleRepo {
 
 impl DbusBleRepo {
     /// Return a new instance of a Dbus ble repo.
-    pub fn new() -> DbusBleRepo {
+    ///
+    /// # Arguments:
+    /// * `adapter` - The name of the adapter to scan with, e.g. hci0. Only the devices found with this adapter are reported.
+    pub fn new(adapter: &str) -> DbusBleRepo {
         let connection = SyncConnection::new_system().expect("Error getting dbus connection");
 
         let mut dbus_ble_repo = DbusBleRepo {
+            adapter_path: format!("{}{}", BLUEZ_DBUS_ADAPTER_PATH_PREFIX, adapter),
             dbus_connection: Arc::new(Mutex::new(connection)),
             found_devices: Arc::new(Mutex::new(Vec::new())),
             on_advertisement_data: Arc::new(Mutex::new(Box::new(|_device| {}))),
@@ -60,7 +70,7 @@ impl DbusBleRepo {
             .get_managed_objects().unwrap();
 
         for (path, payload) in &managed_objects {
-            if payload.contains_key(BLUEZ_DBUS_DEVICE_INTERFACE) {
+            if payload.contains_key(BLUEZ_DBUS_DEVICE_INTERFACE) && Self::is_device_of_adapter(path, &dbus_ble_repo.adapter_path) {
                 let path = path.to_string();
                 let ble_device = Self::get_ble_device(path, &payload[BLUEZ_DBUS_DEVICE_INTERFACE]);
                 dbus_ble_repo.found_devices.lock().unwrap().push(ble_device);
@@ -83,24 +93,61 @@ impl DbusBleRepo {
         dbus_ble_repo
     }
 
+    /// List the ble adapters known by bluez.
+    ///
+    /// Returns the adapters, sorted by name, or an error if bluez cannot be reached, e.g. if there is no system bus.
+    pub fn adapters() -> Result<Vec<BleAdapter>, dbus::Error> {
+        let connection = SyncConnection::new_system()?;
+
+        let managed_objects = connection
+            .with_proxy(BLUEZ_DBUS_DESTINATION, "/", DBUS_CONNECTION_TIMEOUT)
+            .get_managed_objects()?;
+
+        let mut adapters: Vec<BleAdapter> = managed_objects.iter()
+            .filter_map(|(path, payload)| payload.get(BLUEZ_DBUS_ADAPTER_INTERFACE).map(|adapter| (path, adapter)))
+            .map(|(path, adapter)| {
+                let property = |name: &str| adapter.get(name)
+                    .and_then(|value| value.as_str())
+                    .map(String::from)
+                    .unwrap_or_default();
+
+                BleAdapter {
+                    name: Self::get_adapter_from_path(path),
+                    address: property("Address"),
+                    alias: property("Alias"),
+                    powered: matches!(adapter.get("Powered").and_then(|powered| powered.as_i64()), Some(powered) if powered != 0)
+                }
+            })
+            .collect();
+
+        adapters.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(adapters)
+    }
+
+    /// Get the name of the adapter used to scan, e.g. hci0.
+    pub fn adapter(&self) -> &str {
+        &self.adapter_path[BLUEZ_DBUS_ADAPTER_PATH_PREFIX.len()..]
+    }
+
     /// Start the ble scan.
-    pub fn start_scan(&self) {
+    ///
+    /// Returns an error if the discovery could not be started, e.g. if the adapter does not exist.
+    pub fn start_scan(&self) -> Result<(), dbus::Error> {
         self.dbus_connection.lock().unwrap()
-            .with_proxy(BLUEZ_DBUS_DESTINATION, "/org/bluez/hci0", DBUS_CONNECTION_TIMEOUT)
-            .start_discovery().expect("Error starting discovery");
+            .with_proxy(BLUEZ_DBUS_DESTINATION, &self.adapter_path, DBUS_CONNECTION_TIMEOUT)
+            .start_discovery()
     }
 
-    /// Stop the ble scan.
+    /// Stop the ble scan, if the adapter is discovering.
     pub fn stop_scan(&self) {
         let connection = self.dbus_connection.lock().unwrap();
-        let is_discovering = connection
-            .with_proxy(BLUEZ_DBUS_DESTINATION, "/org/bluez/hci0", DBUS_CONNECTION_TIMEOUT)
-            .discovering().unwrap();
-
-        if is_discovering {
-            connection
-                .with_proxy(BLUEZ_DBUS_DESTINATION, "/org/bluez/hci0", DBUS_CONNECTION_TIMEOUT)
-                .stop_discovery().expect("Error stopping discovery");
+        let adapter = connection.with_proxy(BLUEZ_DBUS_DESTINATION, &self.adapter_path, DBUS_CONNECTION_TIMEOUT);
+
+        // The adapter may not exist, or may have been removed, in which case there is nothing to stop.
+        if let Ok(true) = adapter.discovering() {
+            if let Err(error) = adapter.stop_discovery() {
+                warn!("Error stopping the discovery on {}: {}", self.adapter(), error);
+            }
         }
     }
 
@@ -126,14 +173,17 @@ impl DbusBleRepo {
         let on_interface_added = {
             let on_advertisement_data = self.on_advertisement_data.clone();
             let found_devices_clone = self.found_devices.clone();
+            let adapter_path = self.adapter_path.clone();
             move | p: ObjectManagerInterfacesAdded, _: &SyncConnection, _: &Message| {
-                // If this is a ble device which has been discovered
-                if p.interfaces.contains_key(BLUEZ_DBUS_DEVICE_INTERFACE) {
+                let path = p.object.to_string();
+
+                // If this is a ble device which has been discovered with the adapter
+                if p.interfaces.contains_key(BLUEZ_DBUS_DEVICE_INTERFACE) && Self::is_device_of_adapter(&path, &adapter_path) {
                     let mut devices = found_devices_clone.lock().unwrap();
-                    let path = p.object.to_string();
 
                     if let Some(device) = devices.iter_mut().find(|d| d.path == path) {
                         device.service_data = Self::parse_service_data(&p.interfaces[BLUEZ_DBUS_DEVICE_INTERFACE]);
+                        device.manufacturer_data = Self::parse_manufacturer_data(&p.interfaces[BLUEZ_DBUS_DEVICE_INTERFACE]);
                     } else {
                         let device = Self::get_ble_device(path, &p.interfaces[BLUEZ_DBUS_DEVICE_INTERFACE]);
 
@@ -176,7 +226,16 @@ impl DbusBleRepo {
                     let path = m.path().unwrap().to_string();
 
                     if let Some(device) = devices.iter_mut().find(|d| d.path == path) {
-                        device.service_data = Self::parse_service_data(&p.changed_properties);
+                        if p.changed_properties.contains_key("ServiceData") {
+                            device.service_data = Self::parse_service_data(&p.changed_properties);
+                        }
+                        if p.changed_properties.contains_key("ManufacturerData") {
+                            device.manufacturer_data = Self::parse_manufacturer_data(&p.changed_properties);
+                        }
+
+                        if let Some(rssi) = Self::parse_rssi(&p.changed_properties) {
+                            device.rssi = Some(rssi);
+                        }
 
                         (&mut *on_advertisement_data.lock().unwrap())(&device);
                     }
@@ -213,14 +272,82 @@ impl DbusBleRepo {
             }
         }
 
+        let mut address = Self::get_address_from_path(&device_path);
+        if input_interface.contains_key("Address") {
+            match input_interface["Address"].as_str() {
+                None => (),
+                Some(device_address) => address = String::from(device_address)
+            }
+        }
+
+        let adapter = Self::get_adapter_from_path(&device_path);
+
         let path = device_path;
 
+        let rssi = Self::parse_rssi(input_interface);
+
         let service_data = Self::parse_service_data(&input_interface);
 
+        let manufacturer_data = Self::parse_manufacturer_data(input_interface);
+
         BleDevice {
             path,
+            address,
+            adapter,
+            rssi,
             local_name,
-            service_data
+            service_data,
+            manufacturer_data
+        }
+    }
+
+    /// Check whether a device has been found with an adapter.
+    ///
+    /// # Arguments:
+    /// * `device_path` - The dbus path of the device, e.g. /org/bluez/hci0/dev_4C_65_A8_DA_8F_12.
+    /// * `adapter_path` - The dbus path of the adapter, e.g. /org/bluez/hci0.
+    ///
+    /// Returns true whether the device has been found with the adapter, false otherwise.
+    fn is_device_of_adapter(device_path: &str, adapter_path: &str) -> bool {
+        matches!(device_path.strip_prefix(adapter_path), Some(node) if node.starts_with('/'))
+    }
+
+    /// Get the MAC address of a device from its dbus path.
+    ///
+    /// # Arguments:
+    /// * `device_path` - The dbus path of the device, e.g. /org/bluez/hci0/dev_4C_65_A8_DA_8F_12.
+    ///
+    /// Returns the MAC address of the device, e.g. 4C:65:A8:DA:8F:12.
+    fn get_address_from_path(device_path: &str) -> String {
+        match device_path.rsplit('/').next() {
+            Some(node) if node.starts_with("dev_") => node["dev_".len()..].replace('_', ":"),
+            _ => String::from("<unknown>")
+        }
+    }
+
+    /// Get the name of the adapter a device has been found with from the device dbus path.
+    ///
+    /// # Arguments:
+    /// * `device_path` - The dbus path of the device, e.g. /org/bluez/hci0/dev_4C_65_A8_DA_8F_12.
+    ///
+    /// Returns the name of the adapter, e.g. hci0.
+    fn get_adapter_from_path(device_path: &str) -> String {
+        match device_path.split('/').nth(3) {
+            Some(adapter) => String::from(adapter),
+            None => String::from("<unknown>")
+        }
+    }
+
+    /// Parse the received signal strength.
+    ///
+    /// # Arguments:
+    /// * `input` - The input raw data.
+    ///
+    /// Returns the received signal strength in dBm, or None if the input does not contain it.
+    fn parse_rssi(input: &HashMap<String, Variant<Box<dyn RefArg>>>) -> Option<i16> {
+        match input.get("RSSI") {
+            Some(rssi) => rssi.0.as_i64().map(|rssi| rssi as i16),
+            None => None
         }
     }
 
@@ -255,4 +382,33 @@ impl DbusBleRepo {
         }
         output_data
     }
+
+    /// Parse manufacturer specific data.
+    ///
+    /// # Arguments:
+    /// * `input` - The input raw data.
+    ///
+    /// Returns a rust representation of data, by company identifier.
+    fn parse_manufacturer_data(input: &HashMap<String, Variant<Box<dyn RefArg>>>) -> HashMap<u16, Vec<u8>> {
+        let mut output_data : HashMap<u16, Vec<u8>> = HashMap::new();
+        if let Some(manufacturer_data) = input.get("ManufacturerData") {
+            let mut manufacturer_data_iter = manufacturer_data.0.as_iter().unwrap();
+
+            while let (Some(key), Some(value)) = (manufacturer_data_iter.next(), manufacturer_data_iter.next()) {
+                let mut raw_data : Vec<u8> = Vec::new();
+                let inner_value = value.as_iter().unwrap().next().unwrap();
+                for b in inner_value.as_iter().unwrap() {
+                    match b.as_u64() {
+                        None => (),
+                        Some(b) => raw_data.push(b as u8)
+                    }
+                }
+
+                if let Some(company_id) = key.as_u64() {
+                    output_data.insert(company_id as u16, raw_data);
+                }
+            }
+        }
+        output_data
+    }
 }
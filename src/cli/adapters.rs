@@ -0,0 +1,17 @@
+use mijiabt::MijiaBt;
+
+/// List the bluetooth adapters known by bluez.
+pub fn run() -> Result<(), String> {
+    let adapters = MijiaBt::adapters()
+        .map_err(|error| format!("Error listing the bluetooth adapters: {}", error))?;
+    if adapters.is_empty() {
+        println!("No adapter found");
+    }
+
+    for adapter in adapters {
+        let powered = if adapter.powered { "powered" } else { "not powered" };
+        println!("{}  {}  {}  ({})", adapter.name, adapter.address, adapter.alias, powered);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::env;
+use std::ffi::OsStr;
+use std::io;
+use std::path::PathBuf;
+
+use clap::CommandFactory;
+use clap_complete::env::Shells;
+use clap_complete::{CompleteEnv, CompletionCandidate, Shell};
+use clap_mangen::Man;
+
+use mijiabt::sensor::parse_address;
+
+use crate::cli::Cli;
+use crate::cli::config::Config;
+
+/// The environment variable the shells set to request completions from the binary.
+const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Answer the completion request of a shell, if any, and exit.
+/// The completion script of a shell calls the binary back with the COMPLETE environment variable set.
+pub fn complete() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Print the completion script of a shell to the standard output.
+/// The script calls the binary back to complete, so that the sensors of the configuration are read at completion time.
+///
+/// # Arguments:
+/// * `shell` - The shell, e.g. bash.
+pub fn completions(shell: Shell) -> Result<(), String> {
+    let shells = Shells::builtins();
+    let completer = shells.completer(&shell.to_string())
+        .ok_or_else(|| format!("No completion script for {}", shell))?;
+
+    let command = Cli::command();
+    let name = command.get_name();
+
+    completer.write_registration(COMPLETE_VAR, name, name, name, &mut io::stdout())
+        .map_err(|error| format!("Error writing the completion script: {}", error))
+}
+
+/// Complete a device argument with the MAC addresses of the configured sensors, described by their name.
+/// The configuration is loaded from the --config option of the command line being completed, else from its standard paths.
+/// Nothing is completed if it is invalid.
+///
+/// # Arguments:
+/// * `current` - The value being completed.
+///
+/// Returns the MAC addresses starting with the value being completed.
+pub fn complete_sensors(current: &OsStr) -> Vec<CompletionCandidate> {
+    // The shell passes the command line being completed after a -- separator.
+    let args: Vec<String> = env::args().skip_while(|arg| arg != "--").skip(1).collect();
+
+    match Config::load(config_path(&args).as_deref()) {
+        Ok(config) => sensor_candidates(&config, &current.to_string_lossy()),
+        Err(_) => Vec::new()
+    }
+}
+
+/// Get the configuration file given on a command line.
+///
+/// # Arguments:
+/// * `args` - The arguments of the command line.
+///
+/// Returns the path given with --config, or None if there is none.
+fn config_path(args: &[String]) -> Option<PathBuf> {
+    args.iter()
+        .enumerate()
+        .filter_map(|(index, arg)| match arg.strip_prefix("--config=") {
+            Some(path) => Some(path),
+            None if arg == "--config" => args.get(index + 1).map(String::as_str),
+            None => None
+        })
+        .next_back()
+        .map(PathBuf::from)
+}
+
+/// Get the MAC addresses of the configured sensors starting with a prefix, whatever its case and separators.
+///
+/// # Arguments:
+/// * `config` - The configuration.
+/// * `prefix` - The prefix, e.g. a4:c1.
+///
+/// Returns the MAC addresses, upper case and colon separated, described by the name of their sensor.
+fn sensor_candidates(config: &Config, prefix: &str) -> Vec<CompletionCandidate> {
+    let prefix = prefix.to_uppercase().replace('-', ":");
+
+    config.sensors.iter()
+        .map(|sensor| (parse_address(&sensor.address).unwrap_or_else(|| sensor.address.clone()), &sensor.name))
+        .filter(|(address, _)| address.starts_with(&prefix))
+        .map(|(address, name)| CompletionCandidate::new(address).help(Some(name.clone().into())))
+        .collect()
+}
+
+/// Print the man page of the mijiabt binary to the standard output, in roff.
+pub fn man() -> Result<(), String> {
+    Man::new(Cli::command())
+        .render(&mut io::stdout())
+        .map_err(|error| format!("Error writing the man page: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Convert a command line to arguments.
+    fn args(command_line: &str) -> Vec<String> {
+        command_line.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn reads_the_config_option() {
+        assert_eq!(config_path(&args("mijiabt --config /srv/gw.toml read A4")), Some(PathBuf::from("/srv/gw.toml")));
+        assert_eq!(config_path(&args("mijiabt read --config=/srv/gw.toml A4")), Some(PathBuf::from("/srv/gw.toml")));
+        assert_eq!(config_path(&args("mijiabt read A4")), None);
+        assert_eq!(config_path(&args("mijiabt read --config")), None);
+    }
+
+    #[test]
+    fn completes_the_sensor_addresses_whatever_the_case() {
+        let config: Config = toml::from_str(r#"
+            [[sensors]]
+            address = "a4-c1-38-00-00-01"
+            name = "Kitchen"
+
+            [[sensors]]
+            address = "A4:C1:38:00:00:02"
+            name = "Bedroom"
+
+            [[sensors]]
+            address = "58:2D:34:00:00:03"
+            name = "Cellar"
+        "#).unwrap();
+
+        let addresses = |prefix: &str| -> Vec<String> {
+            sensor_candidates(&config, prefix).iter().map(|candidate| candidate.get_value().to_string_lossy().into_owned()).collect()
+        };
+
+        assert_eq!(addresses("a4"), vec!["A4:C1:38:00:00:01", "A4:C1:38:00:00:02"]);
+        assert_eq!(addresses("A4-C1-38-00-00-02"), vec!["A4:C1:38:00:00:02"]);
+        assert_eq!(addresses(""), vec!["A4:C1:38:00:00:01", "A4:C1:38:00:00:02", "58:2D:34:00:00:03"]);
+        assert!(addresses("ff").is_empty());
+
+        let candidates = sensor_candidates(&config, "58");
+        assert_eq!(candidates[0].get_help().map(|help| help.to_string()), Some(String::from("Cellar")));
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use mijiabt::mijiabt_data::Quantity;
+use mijiabt::sensor::{parse_address, Calibration, DeviceFilter, QuantityPolicy, ReportingPolicy, SensorInfo, SensorRegistry};
+
+use crate::cli::output::Format;
+
+/// The path of the configuration file, relative to the user configuration directory.
+const USER_CONFIG_PATH: &str = "mijiabt/config.toml";
+/// The path of the system wide configuration file.
+const SYSTEM_CONFIG_PATH: &str = "/etc/mijiabt/config.toml";
+/// The number of hex digits of a MiBeacon bind key.
+const BIND_KEY_HEX_DIGITS: usize = 32;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+/// The configuration of the mijiabt binary.
+pub struct Config {
+    /// The bluetooth adapter to listen with, e.g. hci0.
+    pub adapter: Option<String>,
+    /// The sensors to listen, by MAC address or alias. If empty, every sensor which is not denied is listened.
+    pub allow: Vec<String>,
+    /// The sensors to ignore, by MAC address or alias.
+    pub deny: Vec<String>,
+    /// The known sensors.
+    pub sensors: Vec<SensorConfig>,
+    /// The outputs the readings are written to. If empty, the readings are written to the standard output.
+    pub sinks: Vec<SinkConfig>,
+    /// The reporting thresholds, by quantity.
+    pub thresholds: ThresholdsConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// The configuration of a sensor.
+pub struct SensorConfig {
+    /// The MAC address of the sensor.
+    pub address: String,
+    /// The display name of the sensor.
+    pub name: String,
+    /// The room the sensor is located in, if any.
+    pub room: Option<String>,
+    /// The calibration of the sensor.
+    #[serde(default)]
+    pub calibration: Calibration,
+    /// The MiBeacon bind key of the sensor, as 32 hex digits, for the sensors sending encrypted advertisements.
+    pub bind_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+/// An output the readings are written to.
+pub enum SinkConfig {
+    /// The standard output.
+    Stdout {
+        /// The output format.
+        #[serde(default = "default_sink_format")]
+        format: Format,
+    },
+    /// A file, appended to.
+    File {
+        /// The path of the file.
+        path: PathBuf,
+        /// The output format.
+        #[serde(default = "default_sink_format")]
+        format: Format,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+/// The reporting thresholds of the quantities.
+pub struct ThresholdsConfig {
+    /// The temperature thresholds, in °C.
+    pub temperature: Option<ThresholdConfig>,
+    /// The humidity thresholds, in %RH.
+    pub humidity: Option<ThresholdConfig>,
+    /// The battery level thresholds, in percent.
+    pub battery: Option<ThresholdConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+/// The reporting thresholds of a quantity, see `QuantityPolicy`.
+pub struct ThresholdConfig {
+    /// The minimum change to report, in the unit of the quantity.
+    pub min_delta: f32,
+    /// The additional change required when the value moves back, in the unit of the quantity.
+    pub hysteresis: f32,
+    /// The minimum time between two reports of a change, in seconds.
+    pub min_interval: Option<u64>,
+    /// The maximum time between two reports, in seconds.
+    pub max_interval: Option<u64>,
+}
+
+/// Get the default output format of a sink.
+fn default_sink_format() -> Format {
+    Format::Json
+}
+
+impl Config {
+    /// Load the configuration.
+    ///
+    /// # Arguments:
+    /// * `path` - The path of the configuration file. If None, the first existing file among
+    ///   `$XDG_CONFIG_HOME/mijiabt/config.toml`, `~/.config/mijiabt/config.toml` and `/etc/mijiabt/config.toml` is loaded.
+    ///
+    /// Returns the validated configuration, the default configuration if no path is given and no file exists,
+    /// or an error if the file cannot be read, parsed or validated.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match Self::standard_paths().into_iter().find(|path| path.is_file()) {
+                Some(path) => path,
+                None => return Ok(Config::default())
+            }
+        };
+
+        let content = fs::read_to_string(&path)
+            .map_err(|error| ConfigError::new(&path, error.to_string()))?;
+
+        let config: Config = toml::from_str(&content)
+            .map_err(|error| ConfigError::new(&path, error.to_string()))?;
+
+        config.validate().map_err(|message| ConfigError::new(&path, message))?;
+
+        Ok(config)
+    }
+
+    /// Get the standard paths of the configuration file, by priority.
+    fn standard_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        if let Some(config_home) = env::var_os("XDG_CONFIG_HOME").filter(|config_home| !config_home.is_empty()) {
+            paths.push(PathBuf::from(config_home).join(USER_CONFIG_PATH));
+        } else if let Some(home) = env::var_os("HOME").filter(|home| !home.is_empty()) {
+            paths.push(PathBuf::from(home).join(".config").join(USER_CONFIG_PATH));
+        }
+
+        paths.push(PathBuf::from(SYSTEM_CONFIG_PATH));
+        paths
+    }
+
+    /// Validate the configuration.
+    ///
+    /// Returns an error message describing the first invalid value, if any.
+    fn validate(&self) -> Result<(), String> {
+        if let Some(adapter) = &self.adapter {
+            if adapter.is_empty() || adapter.contains('/') {
+                return Err(format!("invalid adapter \"{}\", expected a name such as hci0", adapter));
+            }
+        }
+
+        let mut addresses = HashSet::new();
+        for sensor in &self.sensors {
+            let address = parse_address(&sensor.address)
+                .ok_or_else(|| format!("invalid sensor address \"{}\", expected a MAC address such as A4:C1:38:00:00:01", sensor.address))?;
+            if !addresses.insert(address) {
+                return Err(format!("sensor {} is configured twice", sensor.address));
+            }
+            if sensor.name.trim().is_empty() {
+                return Err(format!("sensor {} has an empty name", sensor.address));
+            }
+            if let Some(bind_key) = &sensor.bind_key {
+                if bind_key.len() != BIND_KEY_HEX_DIGITS || !bind_key.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("sensor {} has an invalid bind key, expected {} hex digits", sensor.address, BIND_KEY_HEX_DIGITS));
+                }
+            }
+
+            let calibration = &sensor.calibration;
+            for (quantity, linear_calibration) in [("temperature", calibration.temperature), ("humidity", calibration.humidity)] {
+                if !linear_calibration.offset.is_finite() || !linear_calibration.scale.is_finite() || linear_calibration.scale == 0.0 {
+                    return Err(format!("sensor {} has an invalid {} calibration, expected a finite offset and a finite, non zero scale", sensor.address, quantity));
+                }
+            }
+        }
+
+        for (quantity, threshold) in self.thresholds.iter() {
+            if threshold.min_delta.is_nan() || threshold.min_delta < 0.0 || threshold.hysteresis.is_nan() || threshold.hysteresis < 0.0 {
+                return Err(format!("invalid {} thresholds, min_delta and hysteresis must not be negative", quantity));
+            }
+            if let (Some(min_interval), Some(max_interval)) = (threshold.min_interval, threshold.max_interval) {
+                if min_interval > max_interval {
+                    return Err(format!("invalid {} thresholds, min_interval is greater than max_interval", quantity));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the device filter from the allowed and the denied sensors.
+    pub fn device_filter(&self) -> DeviceFilter {
+        let mut device_filter = DeviceFilter::new();
+        for device in &self.allow {
+            device_filter.allow(device);
+        }
+        for device in &self.deny {
+            device_filter.deny(device);
+        }
+
+        device_filter
+    }
+
+    /// Build the sensor registry from the known sensors.
+    pub fn sensor_registry(&self) -> SensorRegistry {
+        let mut sensor_registry = SensorRegistry::new();
+        for sensor in &self.sensors {
+            let mut info = SensorInfo::new(&sensor.name);
+            info.room = sensor.room.clone();
+
+            sensor_registry.register(&sensor.address, info);
+            sensor_registry.set_calibration(&sensor.address, sensor.calibration);
+        }
+
+        sensor_registry
+    }
+
+    /// Build the reporting policy from the thresholds.
+    pub fn reporting_policy(&self) -> ReportingPolicy {
+        let mut reporting_policy = ReportingPolicy::new();
+        for (quantity, threshold) in [
+            (Quantity::Temperature, self.thresholds.temperature),
+            (Quantity::Humidity, self.thresholds.humidity),
+            (Quantity::Battery, self.thresholds.battery)
+        ] {
+            if let Some(threshold) = threshold {
+                reporting_policy.set(quantity, QuantityPolicy {
+                    min_delta: threshold.min_delta,
+                    hysteresis: threshold.hysteresis,
+                    min_interval: threshold.min_interval.map(Duration::from_secs),
+                    max_interval: threshold.max_interval.map(Duration::from_secs)
+                });
+            }
+        }
+
+        reporting_policy
+    }
+}
+
+impl ThresholdsConfig {
+    /// Iterate over the configured thresholds, along with the name of their quantity.
+    fn iter(&self) -> impl Iterator<Item = (&'static str, ThresholdConfig)> {
+        vec![("temperature", self.temperature), ("humidity", self.humidity), ("battery", self.battery)]
+            .into_iter()
+            .filter_map(|(quantity, threshold)| threshold.map(|threshold| (quantity, threshold)))
+    }
+}
+
+#[derive(Debug)]
+/// An error while loading the configuration.
+pub struct ConfigError {
+    /// The path of the configuration file.
+    path: PathBuf,
+    /// The description of the error.
+    message: String,
+}
+
+impl ConfigError {
+    /// Create a new instance of ConfigError.
+    ///
+    /// # Arguments:
+    /// * `path` - The path of the configuration file.
+    /// * `message` - The description of the error.
+    ///
+    /// Returns a new ConfigError.
+    fn new(path: &Path, message: String) -> ConfigError {
+        ConfigError {
+            path: PathBuf::from(path),
+            message
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid configuration {}: {}", self.path.display(), self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse and validate a configuration.
+    fn parse(content: &str) -> Result<Config, String> {
+        let config: Config = toml::from_str(content).map_err(|error| error.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse a configuration expected to be invalid, and get its error message.
+    fn error(content: &str) -> String {
+        parse(content).expect_err("the configuration should be invalid")
+    }
+
+    const VALID: &str = r#"
+        adapter = "hci1"
+
+        [[sensors]]
+        address = "a4-c1-38-00-00-01"
+        name = "Living room"
+        room = "Ground floor"
+        bind_key = "0123456789abcdef0123456789abcdef"
+        calibration = { temperature = { offset = -0.3 }, humidity = { offset = 2.0, scale = 1.1 } }
+
+        [thresholds.temperature]
+        min_delta = 0.2
+        hysteresis = 0.1
+        min_interval = 60
+        max_interval = 900
+    "#;
+
+    #[test]
+    fn accepts_a_valid_configuration() {
+        let config = parse(VALID).unwrap();
+
+        assert_eq!(config.adapter.as_deref(), Some("hci1"));
+        assert_eq!(config.sensors[0].bind_key.as_deref(), Some("0123456789abcdef0123456789abcdef"));
+    }
+
+    #[test]
+    fn builds_the_sensor_registry() {
+        let sensor_registry = parse(VALID).unwrap().sensor_registry();
+
+        let info = sensor_registry.get("A4:C1:38:00:00:01").unwrap();
+        assert_eq!(info.name, "Living room");
+        assert_eq!(info.room.as_deref(), Some("Ground floor"));
+
+        let calibration = sensor_registry.calibration("A4:C1:38:00:00:01");
+        assert_eq!(calibration.temperature.offset, -0.3);
+        assert_eq!(calibration.temperature.scale, 1.0);
+        assert_eq!(calibration.humidity.offset, 2.0);
+        assert_eq!(calibration.humidity.scale, 1.1);
+    }
+
+    #[test]
+    fn builds_the_reporting_policy() {
+        let reporting_policy = parse(VALID).unwrap().reporting_policy();
+
+        assert_eq!(reporting_policy.get(Quantity::Temperature), QuantityPolicy {
+            min_delta: 0.2,
+            hysteresis: 0.1,
+            min_interval: Some(Duration::from_secs(60)),
+            max_interval: Some(Duration::from_secs(900))
+        });
+        assert_eq!(reporting_policy.get(Quantity::Humidity), QuantityPolicy::default());
+    }
+
+    #[test]
+    fn rejects_a_bad_mac_address() {
+        let message = error("[[sensors]]\naddress = \"A4:C1:38\"\nname = \"Kitchen\"");
+
+        assert!(message.contains("invalid sensor address \"A4:C1:38\""), "{}", message);
+    }
+
+    #[test]
+    fn rejects_a_duplicate_sensor() {
+        let message = error(r#"
+            [[sensors]]
+            address = "A4:C1:38:00:00:01"
+            name = "Kitchen"
+
+            [[sensors]]
+            address = "a4:c1:38:00:00:01"
+            name = "Bedroom"
+        "#);
+
+        assert!(message.contains("sensor a4:c1:38:00:00:01 is configured twice"), "{}", message);
+    }
+
+    #[test]
+    fn rejects_a_bad_bind_key() {
+        let message = error("[[sensors]]\naddress = \"A4:C1:38:00:00:01\"\nname = \"Kitchen\"\nbind_key = \"0123\"");
+
+        assert!(message.contains("invalid bind key, expected 32 hex digits"), "{}", message);
+    }
+
+    #[test]
+    fn rejects_a_zero_scale() {
+        let message = error(r#"
+            [[sensors]]
+            address = "A4:C1:38:00:00:01"
+            name = "Kitchen"
+            calibration = { humidity = { scale = 0.0 } }
+        "#);
+
+        assert!(message.contains("sensor A4:C1:38:00:00:01 has an invalid humidity calibration"), "{}", message);
+    }
+
+    #[test]
+    fn rejects_a_negative_or_nan_threshold() {
+        let message = error("[thresholds.temperature]\nmin_delta = -0.1");
+        assert!(message.contains("invalid temperature thresholds, min_delta and hysteresis must not be negative"), "{}", message);
+
+        let message = error("[thresholds.humidity]\nhysteresis = nan");
+        assert!(message.contains("invalid humidity thresholds, min_delta and hysteresis must not be negative"), "{}", message);
+    }
+
+    #[test]
+    fn rejects_a_min_interval_greater_than_the_max_interval() {
+        let message = error("[thresholds.battery]\nmin_interval = 600\nmax_interval = 60");
+
+        assert!(message.contains("invalid battery thresholds, min_interval is greater than max_interval"), "{}", message);
+    }
+
+    #[test]
+    fn rejects_an_unknown_field() {
+        let message = error("[[sensors]]\naddress = \"A4:C1:38:00:00:01\"\nname = \"Kitchen\"\ncolour = \"blue\"");
+
+        assert!(message.contains("unknown field `colour`"), "{}", message);
+    }
+}
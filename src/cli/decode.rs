@@ -0,0 +1,62 @@
+use mijiabt::advertisement::{decode, DataSource, DecodedFrame};
+
+use crate::cli::output::format_mibeacon;
+
+/// Decode a hex encoded advertisement payload, and print the decoded frame.
+///
+/// # Arguments:
+/// * `source` - Where the payload comes from: a service UUID or a company identifier.
+/// * `payload` - The hex encoded payload. Spaces and colons between the bytes are ignored.
+pub fn run(source: &DataSource, payload: &str) -> Result<(), String> {
+    let data = parse_hex(payload)?;
+
+    match decode(source, &data).map_err(|error| error.to_string())? {
+        DecodedFrame::MiBeacon(mibeacon) => println!("{}", format_mibeacon(&mibeacon)),
+        frame => println!("{:?}", frame)
+    }
+
+    Ok(())
+}
+
+/// Parse a hex encoded byte string.
+///
+/// # Arguments:
+/// * `hex` - The hex encoded bytes. Spaces and colons between the bytes are ignored.
+///
+/// Returns the bytes, or an error message if the string is not valid hex.
+fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace() && *c != ':').collect();
+
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("Odd number of hex digits in {}", hex));
+    }
+
+    digits.chunks(2)
+        .map(|byte| {
+            let byte: String = byte.iter().collect();
+            u8::from_str_radix(&byte, 16).map_err(|_| format!("Invalid hex byte {} in {}", byte, hex))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(parse_hex("5020aa01"), Ok(vec![0x50, 0x20, 0xaa, 0x01]));
+        assert_eq!(parse_hex("50 20:AA 01"), Ok(vec![0x50, 0x20, 0xaa, 0x01]));
+        assert_eq!(parse_hex(""), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn rejects_an_odd_number_of_digits() {
+        assert_eq!(parse_hex("5020a"), Err(String::from("Odd number of hex digits in 5020a")));
+    }
+
+    #[test]
+    fn rejects_invalid_hex() {
+        assert_eq!(parse_hex("50zz"), Err(String::from("Invalid hex byte zz in 50zz")));
+    }
+}
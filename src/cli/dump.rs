@@ -0,0 +1,61 @@
+use mijiabt::advertisement::Advertisement;
+use mijiabt::sensor::DeviceFilter;
+
+use crate::cli::{connect, Options};
+use crate::cli::config::Config;
+use crate::cli::output::{format_mibeacon, to_hex};
+
+/// Print every advertisement received, whatever the device, until the timeout expires or Ctrl-C is pressed.
+/// Only the allowed devices are printed, but the configured allowlist is ignored so that unknown devices show up.
+///
+/// # Arguments:
+/// * `options` - The command line options.
+/// * `config` - The configuration.
+pub fn run(options: &Options, config: &Config) -> Result<(), String> {
+    let mijia_bt = connect(options, config)?;
+
+    let mut device_filter = DeviceFilter::new();
+    for device in &options.allow {
+        device_filter.allow(device);
+    }
+    for device in options.deny.iter().chain(&config.deny) {
+        device_filter.deny(device);
+    }
+
+    mijia_bt.set_on_advertisement_callback(Some(move |advertisement: &Advertisement| {
+        if device_filter.is_allowed(&advertisement.address, &advertisement.alias) {
+            println!("{}", format_advertisement(advertisement));
+        }
+    }));
+
+    mijia_bt.start_listening(options.timeout).map_err(|error| error.to_string())
+}
+
+/// Format an advertisement for display, with its data in hex and the partial decode of its MiBeacon frame.
+///
+/// # Arguments:
+/// * `advertisement` - The advertisement.
+///
+/// Returns the advertisement, on several lines.
+fn format_advertisement(advertisement: &Advertisement) -> String {
+    let rssi = match advertisement.rssi {
+        Some(rssi) => format!("{} dBm", rssi),
+        None => String::from("unknown")
+    };
+
+    let mut lines = vec![format!("{}  {}  \"{}\"  RSSI: {}", advertisement.path, advertisement.address, advertisement.alias, rssi)];
+
+    for (uuid, data) in &advertisement.service_data {
+        lines.push(format!("  service {}: {}", uuid, to_hex(data)));
+    }
+
+    for (company_id, data) in &advertisement.manufacturer_data {
+        lines.push(format!("  manufacturer 0x{:04X}: {}", company_id, to_hex(data)));
+    }
+
+    if let Some(mibeacon) = advertisement.mibeacon() {
+        lines.push(format!("    {}", format_mibeacon(&mibeacon)));
+    }
+
+    lines.join("\n")
+}
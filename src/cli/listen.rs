@@ -0,0 +1,34 @@
+use std::sync::{Arc, Mutex};
+
+use crate::cli::{connect, join_listening, open_outputs, spawn_listening, Options};
+use crate::cli::config::Config;
+use crate::cli::output::Format;
+use crate::cli::reload::reload_on_sighup;
+use crate::cli::systemd::notify_systemd;
+
+/// Stream the readings of the sensors, until the timeout expires or Ctrl-C is pressed.
+/// The configuration is reloaded on SIGHUP, and systemd is notified of the service state when run as a notify service.
+///
+/// # Arguments:
+/// * `options` - The command line options.
+/// * `config` - The configuration.
+/// * `format` - The output format of the readings, or None to write to the configured sinks.
+pub fn run(options: &Options, config: &Config, format: Option<Format>) -> Result<(), String> {
+    let outputs = Arc::new(Mutex::new(open_outputs(config, format)?));
+
+    let mijia_bt = connect(options, config)?;
+    let readings = mijia_bt.readings();
+
+    reload_on_sighup(&mijia_bt, options, config, format, &outputs)?;
+
+    let listening = spawn_listening(&mijia_bt, options.timeout);
+    notify_systemd(&mijia_bt, &listening)?;
+
+    for snapshot in readings {
+        for output in outputs.lock().unwrap().iter_mut() {
+            output.write(&snapshot)?;
+        }
+    }
+
+    join_listening(listening)
+}
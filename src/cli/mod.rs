@@ -0,0 +1,246 @@
+mod adapters;
+mod completions;
+mod config;
+mod decode;
+mod dump;
+mod listen;
+mod output;
+mod read;
+mod reload;
+mod scan;
+mod systemd;
+mod top;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+use clap_complete::{ArgValueCompleter, Shell};
+use log::{info, LevelFilter};
+
+use mijiabt::{ListenError, MijiaBt};
+use mijiabt::advertisement::DataSource;
+
+use crate::cli::completions::complete_sensors;
+use crate::cli::config::Config;
+use crate::cli::output::{Format, Output};
+
+pub use completions::complete;
+
+/// The bluetooth adapter listened with when none is configured.
+const DEFAULT_ADAPTER: &str = "hci0";
+
+#[derive(Debug, Parser)]
+#[command(name = "mijiabt", version, about = "Read Xiaomi Mijia bluetooth thermometers through bluez")]
+/// The command line of the mijiabt binary.
+pub struct Cli {
+    #[command(flatten)]
+    options: Options,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Args)]
+/// The options shared by every subcommand.
+struct Options {
+    /// The configuration file. Defaults to $XDG_CONFIG_HOME/mijiabt/config.toml, then /etc/mijiabt/config.toml.
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// The time to listen for, in seconds.
+    #[arg(long, global = true, value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// The bluetooth adapter to listen with. Defaults to the configured adapter, or hci0.
+    #[arg(long, global = true, value_name = "ADAPTER")]
+    adapter: Option<String>,
+    /// Only listen this sensor, by MAC address or alias. May be repeated.
+    #[arg(long, global = true, value_name = "DEVICE", add = ArgValueCompleter::new(complete_sensors))]
+    allow: Vec<String>,
+    /// Ignore this sensor, by MAC address or alias. May be repeated.
+    #[arg(long, global = true, value_name = "DEVICE", add = ArgValueCompleter::new(complete_sensors))]
+    deny: Vec<String>,
+    /// Log more details to the standard error: -v for info, -vv for debug, -vvv for trace. Overridden by RUST_LOG.
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+}
+
+#[derive(Debug, Subcommand)]
+/// The subcommands of the mijiabt binary.
+enum Command {
+    /// List the nearby supported sensors.
+    Scan,
+    /// Stream the readings of the sensors.
+    Listen {
+        /// The output format of the readings. Overrides the configured sinks.
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+    /// Wait for a single reading of a sensor.
+    Read {
+        /// The MAC address of the sensor.
+        #[arg(add = ArgValueCompleter::new(complete_sensors))]
+        address: String,
+        /// The output format of the reading. Overrides the configured sinks.
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+    /// Decode a hex encoded advertisement payload, offline, e.g. captured with nRF Connect or btmon.
+    Decode {
+        /// The service UUID of the service data, e.g. fe95, or the company identifier of the manufacturer data, e.g. manufacturer:0x038f.
+        source: DataSource,
+        /// The payload, e.g. 5020aa01...
+        payload: String,
+    },
+    /// Print every advertisement received, whatever the device, with its raw data and partial MiBeacon decode.
+    Dump,
+    /// List the bluetooth adapters.
+    Adapters,
+    /// Show a live table of the sensors, with their readings, signal strength and advertisement rate.
+    Top,
+    /// Print the completion script of a shell, e.g. mijiabt completions bash > /etc/bash_completion.d/mijiabt.
+    Completions {
+        /// The shell.
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the man page, e.g. mijiabt man > /usr/share/man/man1/mijiabt.1.
+    Man,
+}
+
+impl Cli {
+    /// Run the subcommand.
+    ///
+    /// Returns an error message if the subcommand failed.
+    pub fn run(self) -> Result<(), String> {
+        init_logging(self.options.verbose);
+
+        let load_config = || Config::load(self.options.config.as_deref()).map_err(|error| error.to_string());
+
+        match &self.command {
+            Command::Scan => scan::run(&self.options, &load_config()?),
+            Command::Listen { format } => listen::run(&self.options, &load_config()?, *format),
+            Command::Read { address, format } => read::run(&self.options, &load_config()?, address, *format),
+            Command::Decode { source, payload } => decode::run(source, payload),
+            Command::Dump => dump::run(&self.options, &load_config()?),
+            Command::Adapters => adapters::run(),
+            Command::Top => top::run(&self.options, &load_config()?),
+            Command::Completions { shell } => completions::completions(*shell),
+            Command::Man => completions::man()
+        }
+    }
+}
+
+/// Initialize the logger, which writes to the standard error.
+///
+/// # Arguments:
+/// * `verbose` - The number of -v flags: 0 logs the warnings and the errors only, 1 the info, 2 the debug
+///   and 3 or more the trace messages. The RUST_LOG environment variable, if set, takes precedence.
+fn init_logging(verbose: u8) {
+    let level = match verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace
+    };
+
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_env("RUST_LOG")
+        .init();
+}
+
+/// Connect to the sensors with the adapter and the device filter of the options and the configuration.
+/// The sensor names, calibrations and reporting thresholds of the configuration are applied.
+/// Ctrl-C stops listening.
+///
+/// # Arguments:
+/// * `options` - The command line options.
+/// * `config` - The configuration.
+///
+/// Returns the mijia bt sensor abstraction, or an error message if the adapter does not exist.
+fn connect(options: &Options, config: &Config) -> Result<Arc<MijiaBt>, String> {
+    let adapter = options.adapter.as_deref()
+        .or(config.adapter.as_deref())
+        .unwrap_or(DEFAULT_ADAPTER);
+
+    let adapters = MijiaBt::adapters()
+        .map_err(|error| format!("Error listing the bluetooth adapters: {}", error))?;
+    if !adapters.iter().any(|known_adapter| known_adapter.name == adapter) {
+        return Err(format!("Unknown bluetooth adapter {}, see mijiabt adapters", adapter));
+    }
+
+    let mijia_bt = Arc::new(MijiaBt::with_adapter(adapter));
+
+    apply_config(&mijia_bt, options, config);
+
+    ctrlc::set_handler({
+        let mijia_bt_clone = mijia_bt.clone();
+        move || {
+            mijia_bt_clone.stop_listening();
+            info!("SIGINT received. Exiting...");
+        }
+    }).expect("Error setting Ctrl-C handler");
+
+    Ok(mijia_bt)
+}
+
+/// Apply the device filter of the options and the configuration, and the sensor names, calibrations
+/// and reporting thresholds of the configuration, to a connected mijia bt sensor abstraction.
+///
+/// # Arguments:
+/// * `mijia_bt` - The mijia bt sensor abstraction.
+/// * `options` - The command line options.
+/// * `config` - The configuration.
+fn apply_config(mijia_bt: &MijiaBt, options: &Options, config: &Config) {
+    mijia_bt.set_sensor_registry(config.sensor_registry());
+    mijia_bt.set_reporting_policy(config.reporting_policy());
+
+    let mut device_filter = config.device_filter();
+    for device in &options.allow {
+        device_filter.allow(device);
+    }
+    for device in &options.deny {
+        device_filter.deny(device);
+    }
+    mijia_bt.set_device_filter(device_filter);
+}
+
+/// Listen the sensors in the background.
+///
+/// # Arguments:
+/// * `mijia_bt` - The mijia bt sensor abstraction.
+/// * `timeout` - The time in seconds to listen for, or None to listen until stopped.
+///
+/// Returns the handle of the listening thread.
+fn spawn_listening(mijia_bt: &Arc<MijiaBt>, timeout: Option<u64>) -> JoinHandle<Result<(), ListenError>> {
+    let mijia_bt = mijia_bt.clone();
+    thread::spawn(move || mijia_bt.start_listening(timeout))
+}
+
+/// Wait for the listening thread to end.
+///
+/// # Arguments:
+/// * `listening` - The handle of the listening thread.
+///
+/// Returns an error message if the listening could not be started or the thread panicked.
+fn join_listening(listening: JoinHandle<Result<(), ListenError>>) -> Result<(), String> {
+    listening.join()
+        .map_err(|_| String::from("The listening thread panicked"))?
+        .map_err(|error| error.to_string())
+}
+
+/// Open the outputs the readings are written to.
+///
+/// # Arguments:
+/// * `config` - The configuration.
+/// * `format` - The output format given on the command line, if any. It overrides the configured sinks.
+///
+/// Returns the outputs: the standard output in the given format, else the configured sinks,
+/// else the standard output in the human format. Returns an error message if a sink cannot be opened.
+fn open_outputs(config: &Config, format: Option<Format>) -> Result<Vec<Output>, String> {
+    match format {
+        Some(format) => Ok(vec![Output::stdout(format)]),
+        None if config.sinks.is_empty() => Ok(vec![Output::stdout(Format::Human)]),
+        None => config.sinks.iter().map(Output::open).collect()
+    }
+}
@@ -0,0 +1,425 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::time::UNIX_EPOCH;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use mijiabt::advertisement::MiBeacon;
+use mijiabt::mijiabt_data::{Quantity, Reading, ReadingEvent, ReadingSnapshot};
+
+use crate::cli::config::SinkConfig;
+
+/// The quantities written in the csv and tsv columns, in order.
+const COLUMN_QUANTITIES: [(Quantity, &str); 4] = [
+    (Quantity::Temperature, "temperature_celsius"),
+    (Quantity::Humidity, "humidity_percent"),
+    (Quantity::Battery, "battery_percent"),
+    (Quantity::Voltage, "voltage_volts"),
+];
+
+/// The columns written after the quantities in the csv and tsv formats, in order.
+/// They follow the quantities so that the columns of the files written by earlier versions keep their position.
+const EXTRA_COLUMNS: [&str; 7] = [
+    "room",
+    "tags",
+    "dew_point_celsius",
+    "absolute_humidity_g_m3",
+    "heat_index_celsius",
+    "humidex",
+    "vapour_pressure_deficit_kpa",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The output format of the readings.
+pub enum Format {
+    /// One human readable line per reading.
+    Human,
+    /// One JSON reading event per line, see `ReadingEvent`.
+    Json,
+    /// Comma separated values, with a header.
+    Csv,
+    /// Tab separated values, with a header.
+    Tsv,
+}
+
+/// A writer of readings, in a format.
+pub struct Output {
+    /// The output format.
+    format: Format,
+    /// The writer the readings are written to.
+    writer: Box<dyn Write + Send>,
+    /// Whether the header has been written or not.
+    header_written: bool,
+    /// Whether the readings are written to the standard output.
+    is_stdout: bool,
+}
+
+impl Output {
+    /// Create a new instance of Output writing to the standard output.
+    ///
+    /// # Arguments:
+    /// * `format` - The output format.
+    ///
+    /// Returns a new Output, which did not write anything yet.
+    pub fn stdout(format: Format) -> Output {
+        Output {
+            format,
+            writer: Box::new(io::stdout()),
+            header_written: false,
+            is_stdout: true
+        }
+    }
+
+    /// Create a new instance of Output writing to a sink.
+    /// A file is appended to, and the header is only written if it is empty.
+    ///
+    /// # Arguments:
+    /// * `sink` - The configuration of the sink.
+    ///
+    /// Returns a new Output, or an error message if the file cannot be opened.
+    pub fn open(sink: &SinkConfig) -> Result<Output, String> {
+        match sink {
+            SinkConfig::Stdout { format } => Ok(Output::stdout(*format)),
+            SinkConfig::File { path, format } => {
+                let file = OpenOptions::new().create(true).append(true).open(path)
+                    .map_err(|error| format!("Error opening {}: {}", path.display(), error))?;
+                let header_written = matches!(file.metadata(), Ok(metadata) if metadata.len() > 0);
+
+                Ok(Output {
+                    format: *format,
+                    writer: Box::new(file),
+                    header_written,
+                    is_stdout: false
+                })
+            }
+        }
+    }
+
+    /// Carry on the standard output stream of a previous output in the same format, e.g. on a configuration reload,
+    /// so that the header is not written again in the middle of the stream.
+    ///
+    /// # Arguments:
+    /// * `previous_outputs` - The outputs replaced by this one.
+    pub fn continue_stream(&mut self, previous_outputs: &[Output]) {
+        if self.is_stdout && previous_outputs.iter().any(|output| output.is_stdout && output.format == self.format && output.header_written) {
+            self.header_written = true;
+        }
+    }
+
+    /// Write a reading, preceded by the header if this is the first one.
+    ///
+    /// # Arguments:
+    /// * `snapshot` - The snapshot of the reading.
+    ///
+    /// Returns an error message if the reading could not be formatted or written.
+    pub fn write(&mut self, snapshot: &ReadingSnapshot) -> Result<(), String> {
+        let line = match self.format {
+            Format::Human => format!("{}{}: {}", snapshot.name(), format_location(&ReadingEvent::from(snapshot)), format_reading(snapshot.reading())),
+            Format::Json => ReadingEvent::from(snapshot).to_json().map_err(|error| error.to_string())?,
+            Format::Csv => self.format_record(snapshot, ','),
+            Format::Tsv => self.format_record(snapshot, '\t')
+        };
+
+        writeln!(self.writer, "{}", line)
+            .and_then(|_| self.writer.flush())
+            .map_err(|error| format!("Error writing a reading: {}", error))
+    }
+
+    /// Format a reading as delimiter separated values, preceded by the header if this is the first one.
+    ///
+    /// # Arguments:
+    /// * `snapshot` - The snapshot of the reading.
+    /// * `delimiter` - The field delimiter.
+    ///
+    /// Returns the record, along with the header line if needed.
+    fn format_record(&mut self, snapshot: &ReadingSnapshot, delimiter: char) -> String {
+        let mut lines = Vec::new();
+
+        if !self.header_written {
+            let mut header = vec!["device_id", "name", "timestamp"];
+            header.extend(COLUMN_QUANTITIES.iter().map(|(_, column)| *column));
+            header.extend(EXTRA_COLUMNS.iter());
+
+            lines.push(header.join(&delimiter.to_string()));
+            self.header_written = true;
+        }
+
+        let event = ReadingEvent::from(snapshot);
+        let timestamp = event.timestamp()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis());
+
+        let mut fields = vec![
+            escape_field(event.device_id(), delimiter),
+            escape_field(event.name(), delimiter),
+            timestamp.to_string()
+        ];
+        fields.extend(COLUMN_QUANTITIES.iter().map(|(quantity, _)| {
+            quantity.value(snapshot.reading()).map_or(String::new(), |value| value.to_string())
+        }));
+
+        fields.push(escape_field(event.room().unwrap_or_default(), delimiter));
+        fields.push(escape_field(&format_tags(&event), delimiter));
+
+        let climate = event.climate().cloned().unwrap_or_default();
+        fields.extend([
+            climate.dew_point,
+            climate.absolute_humidity,
+            climate.heat_index,
+            climate.humidex,
+            climate.vapour_pressure_deficit
+        ].iter().map(|measurement| measurement.as_ref().map_or(String::new(), |measurement| measurement.value.to_string())));
+
+        lines.push(fields.join(&delimiter.to_string()));
+        lines.join("\n")
+    }
+}
+
+/// Format a reading for display.
+///
+/// # Arguments:
+/// * `reading` - The reading.
+///
+/// Returns the present values of the reading, separated by commas.
+pub fn format_reading(reading: &Reading) -> String {
+    let mut values = Vec::new();
+    if let Some(temperature) = reading.temperature {
+        values.push(format!("Temperature: {}", temperature));
+    }
+    if let Some(humidity) = reading.humidity {
+        values.push(format!("Humidity: {}", humidity));
+    }
+    if let Some(climate) = reading.climate_metrics() {
+        if let Some(dew_point) = climate.dew_point {
+            values.push(format!("Dew point: {}", dew_point));
+        }
+        if let Some(absolute_humidity) = climate.absolute_humidity {
+            values.push(format!("Absolute humidity: {:.1} g/m³", absolute_humidity));
+        }
+        if let Some(heat_index) = climate.heat_index {
+            values.push(format!("Heat index: {}", heat_index));
+        }
+        if let Some(humidex) = climate.humidex {
+            values.push(format!("Humidex: {:.1}", humidex));
+        }
+        if let Some(vapour_pressure_deficit) = climate.vapour_pressure_deficit {
+            values.push(format!("VPD: {:.2} kPa", vapour_pressure_deficit));
+        }
+    }
+    if let Some(battery) = reading.battery {
+        values.push(format!("Battery: {}", battery));
+    }
+
+    if values.is_empty() {
+        String::from("No value")
+    } else {
+        values.join(", ")
+    }
+}
+
+/// Format the room and the tags of the sensor of a reading for display.
+///
+/// # Arguments:
+/// * `event` - The reading event.
+///
+/// Returns the room and the tags within parentheses, preceded by a space, or an empty string if there are none.
+fn format_location(event: &ReadingEvent) -> String {
+    let mut location: Vec<String> = event.room().map(String::from).into_iter().collect();
+    if !event.tags().is_empty() {
+        location.push(format_tags(event));
+    }
+
+    if location.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", location.join(", "))
+    }
+}
+
+/// Format the tags of the sensor of a reading.
+///
+/// # Arguments:
+/// * `event` - The reading event.
+///
+/// Returns the tags as key=value pairs separated by semicolons, e.g. floor=0;zone=north.
+fn format_tags(event: &ReadingEvent) -> String {
+    event.tags().iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+/// Format the partial decode of a MiBeacon frame for display.
+///
+/// # Arguments:
+/// * `mibeacon` - The MiBeacon frame.
+///
+/// Returns the fields of the frame which could be decoded.
+pub fn format_mibeacon(mibeacon: &MiBeacon) -> String {
+    let mut fields = vec![
+        format!("frame control 0x{:04X}", mibeacon.frame_control),
+        format!("product 0x{:04X}", mibeacon.product_id),
+        format!("frame {}", mibeacon.frame_counter)
+    ];
+
+    if let Some(address) = &mibeacon.address {
+        fields.push(format!("mac {}", address));
+    }
+    if let Some(capability) = mibeacon.capability {
+        fields.push(format!("capability 0x{:02X}", capability));
+    }
+    if mibeacon.is_encrypted() {
+        fields.push(String::from("encrypted"));
+    }
+    if let Some(object) = &mibeacon.object {
+        fields.push(format!("object 0x{:04X} ({} bytes): {}", object.object_type, object.length, to_hex(&object.data)));
+    }
+    if let Some(reading) = mibeacon.reading() {
+        fields.push(format_reading(&reading));
+    }
+
+    format!("MiBeacon: {}", fields.join(", "))
+}
+
+/// Format bytes in hex.
+///
+/// # Arguments:
+/// * `data` - The bytes.
+///
+/// Returns the bytes in lower case hex, separated by spaces.
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().join(" ")
+}
+
+/// Escape a field of delimiter separated values.
+///
+/// # Arguments:
+/// * `field` - The field.
+/// * `delimiter` - The field delimiter.
+///
+/// Returns the field, quoted for csv when it contains the delimiter, a quote or a line break,
+/// or with the delimiters and line breaks replaced by spaces for tsv.
+fn escape_field(field: &str, delimiter: char) -> String {
+    let special = |c: char| c == delimiter || c == '"' || c == '\n' || c == '\r';
+
+    if delimiter == '\t' {
+        field.replace(['\t', '\n', '\r'], " ")
+    } else if field.contains(special) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mijiabt::mijiabt_data::{BatteryLevel, Humidity, ReceiveMetadata, Temperature};
+    use mijiabt::sensor::SensorInfo;
+
+    use super::*;
+
+    /// Build the snapshot of a reading of a sensor.
+    ///
+    /// # Arguments:
+    /// * `name` - The display name of the sensor.
+    fn snapshot(name: &str) -> ReadingSnapshot {
+        let mut info = SensorInfo::new(name);
+        info.room = Some(String::from("Ground floor"));
+        info.tags.insert(String::from("floor"), String::from("0"));
+
+        let reading = Reading {
+            temperature: Some(Temperature::from_tenths(215)),
+            humidity: Some(Humidity::from_tenths(452)),
+            battery: Some(BatteryLevel::from_percent(87)),
+            voltage: None
+        };
+
+        ReadingSnapshot::new("A4:C1:38:00:00:01", Some(info), reading, reading, ReceiveMetadata::now(Some(3), Some(-72), "hci0"))
+    }
+
+    /// Build an output discarding what it writes.
+    fn output(format: Format) -> Output {
+        Output {
+            format,
+            writer: Box::new(io::sink()),
+            header_written: false,
+            is_stdout: false
+        }
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        assert_eq!(escape_field("Living room", ','), "Living room");
+        assert_eq!(escape_field("Living, \"big\"\nroom", ','), "\"Living, \"\"big\"\"\nroom\"");
+        assert_eq!(escape_field("Say \"hi\"", ','), "\"Say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn quotes_the_name_of_a_csv_record() {
+        let record = output(Format::Csv).format_record(&snapshot("Living, \"big\"\nroom"), ',');
+
+        assert!(record.contains("\nA4:C1:38:00:00:01,\"Living, \"\"big\"\"\nroom\","), "{}", record);
+    }
+
+    #[test]
+    fn replaces_tsv_delimiters_and_line_breaks() {
+        assert_eq!(escape_field("Living\troom\nup\rstairs, \"big\"", '\t'), "Living room up stairs, \"big\"");
+    }
+
+    #[test]
+    fn writes_the_csv_header_once() {
+        let mut output = output(Format::Csv);
+
+        let first = output.format_record(&snapshot("Kitchen"), ',');
+        let second = output.format_record(&snapshot("Kitchen"), ',');
+
+        assert_eq!(first.lines().count(), 2);
+        assert_eq!(second.lines().count(), 1);
+        assert_eq!(first.lines().next(), Some("device_id,name,timestamp,temperature_celsius,humidity_percent,battery_percent,voltage_volts,\
+            room,tags,dew_point_celsius,absolute_humidity_g_m3,heat_index_celsius,humidex,vapour_pressure_deficit_kpa"));
+    }
+
+    #[test]
+    fn writes_the_fields_in_the_header_order() {
+        let record = output(Format::Tsv).format_record(&snapshot("Living\troom"), '\t');
+        let lines: Vec<Vec<&str>> = record.lines().map(|line| line.split('\t').collect()).collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), lines[1].len());
+
+        let field = |column: &str| lines[1][lines[0].iter().position(|header| *header == column).unwrap()];
+        assert_eq!(field("device_id"), "A4:C1:38:00:00:01");
+        assert_eq!(field("name"), "Living room");
+        assert_eq!(field("temperature_celsius"), "21.5");
+        assert_eq!(field("humidity_percent"), "45.2");
+        assert_eq!(field("battery_percent"), "87");
+        assert_eq!(field("voltage_volts"), "");
+        assert_eq!(field("room"), "Ground floor");
+        assert_eq!(field("tags"), "floor=0");
+        assert!(!field("dew_point_celsius").is_empty());
+    }
+
+    #[test]
+    fn does_not_write_the_header_again_on_the_same_stdout_stream() {
+        let mut previous_output = Output::stdout(Format::Csv);
+        previous_output.header_written = true;
+
+        let mut output = Output::stdout(Format::Csv);
+        output.continue_stream(&[previous_output]);
+
+        assert!(output.header_written);
+    }
+
+    #[test]
+    fn writes_the_header_of_a_new_stdout_format() {
+        let mut previous_output = Output::stdout(Format::Csv);
+        previous_output.header_written = true;
+
+        let mut output = Output::stdout(Format::Tsv);
+        output.continue_stream(&[previous_output]);
+
+        assert!(!output.header_written);
+    }
+}
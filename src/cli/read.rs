@@ -0,0 +1,46 @@
+use mijiabt::sensor::parse_address;
+
+use crate::cli::{connect, join_listening, open_outputs, spawn_listening, Options};
+use crate::cli::config::Config;
+use crate::cli::output::Format;
+
+/// The time to wait for a reading when no timeout is given, in seconds.
+const DEFAULT_READ_TIMEOUT_S: u64 = 60;
+
+/// Wait for a single reading of a sensor, and print it.
+///
+/// # Arguments:
+/// * `options` - The command line options.
+/// * `config` - The configuration.
+/// * `address` - The MAC address of the sensor, separated by colons or dashes.
+/// * `format` - The output format of the reading, or None to write to the configured sinks.
+pub fn run(options: &Options, config: &Config, address: &str, format: Option<Format>) -> Result<(), String> {
+    let address = parse_address(address)
+        .ok_or_else(|| format!("Invalid sensor address \"{}\", expected a MAC address such as A4:C1:38:00:00:01", address))?;
+
+    let mut outputs = open_outputs(config, format)?;
+
+    let options_with_address = Options {
+        config: options.config.clone(),
+        timeout: Some(options.timeout.unwrap_or(DEFAULT_READ_TIMEOUT_S)),
+        adapter: options.adapter.clone(),
+        allow: vec![address.clone()],
+        deny: options.deny.clone(),
+        verbose: options.verbose
+    };
+
+    let mijia_bt = connect(&options_with_address, config)?;
+    let mut readings = mijia_bt.readings();
+
+    let listening = spawn_listening(&mijia_bt, options_with_address.timeout);
+
+    let snapshot = readings.find(|snapshot| snapshot.address() == address);
+
+    mijia_bt.stop_listening();
+    join_listening(listening)?;
+
+    match snapshot {
+        Some(snapshot) => outputs.iter_mut().try_for_each(|output| output.write(&snapshot)),
+        None => Err(format!("No reading received from {}", address))
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use signal_hook::consts::SIGHUP;
+use signal_hook::iterator::Signals;
+
+use log::{error, info, warn};
+
+use mijiabt::MijiaBt;
+
+use crate::cli::{apply_config, open_outputs, Options};
+use crate::cli::config::Config;
+use crate::cli::output::{Format, Output};
+
+/// Reload the configuration on SIGHUP, in the background.
+/// The device filter, the sensor names, the calibrations, the reporting thresholds and the sinks are applied
+/// to the running mijia bt sensor abstraction, keeping its D-Bus connection and its discovered sensors.
+/// An invalid configuration is reported and ignored, keeping the current one.
+///
+/// # Arguments:
+/// * `mijia_bt` - The mijia bt sensor abstraction.
+/// * `options` - The command line options.
+/// * `config` - The configuration currently applied.
+/// * `format` - The output format given on the command line, if any. If given, the sinks are not reloaded.
+/// * `outputs` - The outputs the readings are written to, replaced by the reloaded sinks.
+///
+/// Returns an error message if the SIGHUP handler cannot be registered.
+pub fn reload_on_sighup(mijia_bt: &Arc<MijiaBt>, options: &Options, config: &Config, format: Option<Format>, outputs: &Arc<Mutex<Vec<Output>>>) -> Result<(), String> {
+    let mut signals = Signals::new([SIGHUP])
+        .map_err(|error| format!("Error setting SIGHUP handler: {}", error))?;
+
+    let mijia_bt = mijia_bt.clone();
+    let options = options.clone();
+    let adapter = config.adapter.clone();
+    let outputs = outputs.clone();
+
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            let config = match Config::load(options.config.as_deref()) {
+                Ok(config) => config,
+                Err(error) => {
+                    error!("SIGHUP received. {}, keeping the current configuration", error);
+                    continue;
+                }
+            };
+
+            if format.is_none() {
+                match open_outputs(&config, None) {
+                    Ok(mut reloaded_outputs) => {
+                        let mut outputs = outputs.lock().unwrap();
+                        for reloaded_output in reloaded_outputs.iter_mut() {
+                            reloaded_output.continue_stream(&outputs);
+                        }
+                        *outputs = reloaded_outputs;
+                    },
+                    Err(error) => {
+                        error!("SIGHUP received. {}, keeping the current configuration", error);
+                        continue;
+                    }
+                }
+            }
+
+            apply_config(&mijia_bt, &options, &config);
+
+            if options.adapter.is_none() && config.adapter != adapter {
+                warn!("The adapter change is only applied on restart");
+            }
+
+            info!("SIGHUP received. Configuration reloaded");
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use mijiabt::MijiaBt;
+use mijiabt::sensor::SensorStatistics;
+
+use crate::cli::{connect, Options};
+use crate::cli::config::Config;
+
+/// The time to scan for when no timeout is given, in seconds.
+const DEFAULT_SCAN_TIMEOUT_S: u64 = 10;
+
+/// List the nearby supported sensors, with their model, MAC address and signal strength,
+/// along with their reception statistics.
+///
+/// # Arguments:
+/// * `options` - The command line options.
+/// * `config` - The configuration.
+pub fn run(options: &Options, config: &Config) -> Result<(), String> {
+    let mijia_bt = connect(options, config)?;
+
+    mijia_bt.start_listening(Some(options.timeout.unwrap_or(DEFAULT_SCAN_TIMEOUT_S)))
+        .map_err(|error| error.to_string())?;
+
+    let statistics = mijia_bt.all_statistics();
+    if statistics.is_empty() {
+        println!("No sensor found");
+    }
+
+    for (address, statistics) in statistics {
+        let rssi = match statistics.rssi_mean() {
+            Some(rssi) => format!("{:.0} dBm", rssi),
+            None => String::from("unknown")
+        };
+
+        println!("{}  {}  RSSI: {}", MijiaBt::SUPPORTED_MODEL, address, rssi);
+        println!("  {}", format_statistics(&statistics));
+    }
+
+    Ok(())
+}
+
+/// Format the reception statistics of a sensor for display.
+///
+/// # Arguments:
+/// * `statistics` - The reception statistics.
+///
+/// Returns the advertisement rate, the decoded and undecodable frames, the last seen time,
+/// the RSSI distribution and the link quality, on a single line.
+fn format_statistics(statistics: &SensorStatistics) -> String {
+    let link_quality = statistics.link_quality();
+
+    format!(
+        "{} advertisements ({}/min), {} decoded, {} undecodable, last seen {}s ago, RSSI min/avg/max: {}/{}/{} dBm, {} lost ({:.1}%), {} duplicates",
+        statistics.advertisements(),
+        statistics.advertisements_per_minute(),
+        statistics.decoded_frames(),
+        statistics.undecodable_frames(),
+        statistics.last_seen().elapsed().as_secs(),
+        statistics.rssi_min().map_or(String::from("-"), |rssi| rssi.to_string()),
+        statistics.rssi_mean().map_or(String::from("-"), |rssi| format!("{:.0}", rssi)),
+        statistics.rssi_max().map_or(String::from("-"), |rssi| rssi.to_string()),
+        link_quality.lost,
+        link_quality.loss_ratio() * 100.0,
+        link_quality.duplicates
+    )
+}
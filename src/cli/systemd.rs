@@ -0,0 +1,259 @@
+use std::env;
+use std::io;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::process;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use signal_hook::consts::SIGTERM;
+use signal_hook::iterator::Signals;
+
+use mijiabt::{ListenError, MijiaBt};
+
+/// The period of the status updates when the watchdog is disabled, in seconds.
+const STATUS_PERIOD_S: u64 = 10;
+/// The period the listening is checked at while waiting for the discovery to start, in milliseconds.
+const READY_POLL_PERIOD_MS: u64 = 20;
+/// The notification sent once the discovery has started.
+const READY: &str = "READY=1";
+/// The notification sent to reset the watchdog timer.
+const WATCHDOG: &str = "WATCHDOG=1";
+/// The notification sent when the service begins its shutdown.
+const STOPPING: &str = "STOPPING=1";
+
+/// A sender of service notifications to systemd, see sd_notify(3).
+pub struct Notifier {
+    /// The socket the notifications are sent from.
+    socket: UnixDatagram,
+    /// The address of the systemd notification socket.
+    address: SocketAddr,
+}
+
+impl Notifier {
+    /// Create a new instance of Notifier from the NOTIFY_SOCKET environment variable.
+    ///
+    /// Returns a new Notifier, or None if the process is not run by systemd with notifications enabled.
+    pub fn from_env() -> Option<Notifier> {
+        let path = env::var("NOTIFY_SOCKET").ok().filter(|path| !path.is_empty())?;
+
+        match Notifier::new(&path) {
+            Ok(notifier) => Some(notifier),
+            Err(error) => {
+                error!("Error opening the systemd notification socket {}: {}", path, error);
+                None
+            }
+        }
+    }
+
+    /// Create a new instance of Notifier sending to a socket.
+    ///
+    /// # Arguments:
+    /// * `path` - The path of the notification socket. A path starting with @ is an abstract socket.
+    pub fn new(path: &str) -> io::Result<Notifier> {
+        let address = match path.strip_prefix('@') {
+            Some(name) => SocketAddr::from_abstract_name(name.as_bytes())?,
+            None => SocketAddr::from_pathname(path)?
+        };
+
+        Ok(Notifier { socket: UnixDatagram::unbound()?, address })
+    }
+
+    /// Send a notification.
+    ///
+    /// # Arguments:
+    /// * `state` - The newline separated variable assignments, e.g. READY=1.
+    pub fn notify(&self, state: &str) -> io::Result<()> {
+        self.socket.send_to_addr(state.as_bytes(), &self.address).map(|_| ())
+    }
+}
+
+/// Format the status notification of the service.
+///
+/// # Arguments:
+/// * `active_sensors` - The number of active sensors.
+fn status(active_sensors: usize) -> String {
+    format!("STATUS=Listening, {} active sensors", active_sensors)
+}
+
+/// Send a notification, logging the error if any.
+///
+/// # Arguments:
+/// * `notifier` - The systemd notifier.
+/// * `state` - The newline separated variable assignments, e.g. READY=1.
+fn send(notifier: &Notifier, state: &str) {
+    if let Err(error) = notifier.notify(state) {
+        error!("Error notifying systemd: {}", error);
+    }
+}
+
+/// Get the watchdog timeout requested by systemd.
+///
+/// # Arguments:
+/// * `usec` - The value of the WATCHDOG_USEC environment variable, if set: the timeout in microseconds.
+/// * `pid` - The value of the WATCHDOG_PID environment variable, if set: the process the watchdog is meant for.
+///
+/// Returns the watchdog timeout, or None if the watchdog is disabled or meant for another process.
+fn watchdog_timeout(usec: Option<&str>, pid: Option<&str>) -> Option<Duration> {
+    if let Some(pid) = pid {
+        if pid.parse::<u32>().ok() != Some(process::id()) {
+            return None;
+        }
+    }
+
+    usec.and_then(|usec| usec.parse::<u64>().ok())
+        .filter(|usec| *usec > 0)
+        .map(Duration::from_micros)
+}
+
+/// Decide whether to ping the watchdog, i.e. whether bluez advertisements have been received within the watchdog timeout.
+///
+/// # Arguments:
+/// * `last_activity` - The last time an advertisement has been received, if any.
+/// * `ready_at` - The time the service got ready, standing for the last activity until an advertisement is received.
+/// * `watchdog` - The watchdog timeout.
+/// * `now` - The current time.
+fn should_ping(last_activity: Option<Instant>, ready_at: Instant, watchdog: Duration, now: Instant) -> bool {
+    now.saturating_duration_since(last_activity.unwrap_or(ready_at)) < watchdog
+}
+
+/// Notify systemd of the service state, in the background, if the process is run with notifications enabled.
+/// READY=1 is sent once the discovery has started, then STATUS= with the number of active sensors is sent periodically.
+/// If the watchdog is enabled, WATCHDOG=1 is sent at half the watchdog timeout, as long as bluez advertisements
+/// have been received within the timeout, so that systemd restarts the service if the bluez events stop flowing.
+/// On SIGTERM, which systemd stops the service with, STOPPING=1 is sent and the listening is stopped.
+///
+/// # Arguments:
+/// * `mijia_bt` - The mijia bt sensor abstraction.
+/// * `listening` - The handle of the listening thread.
+///
+/// Returns an error message if the SIGTERM handler cannot be registered.
+pub fn notify_systemd(mijia_bt: &Arc<MijiaBt>, listening: &JoinHandle<Result<(), ListenError>>) -> Result<(), String> {
+    let notifier = match Notifier::from_env() {
+        Some(notifier) => Arc::new(notifier),
+        None => return Ok(())
+    };
+
+    let mut signals = Signals::new([SIGTERM])
+        .map_err(|error| format!("Error setting SIGTERM handler: {}", error))?;
+
+    thread::spawn({
+        let notifier = notifier.clone();
+        let mijia_bt = mijia_bt.clone();
+        move || {
+            if signals.forever().next().is_some() {
+                send(&notifier, STOPPING);
+                mijia_bt.stop_listening();
+                info!("SIGTERM received. Exiting...");
+            }
+        }
+    });
+
+    while !mijia_bt.is_listening() {
+        if listening.is_finished() {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(READY_POLL_PERIOD_MS));
+    }
+
+    let watchdog = watchdog_timeout(env::var("WATCHDOG_USEC").ok().as_deref(), env::var("WATCHDOG_PID").ok().as_deref());
+    let period = watchdog.map_or(Duration::from_secs(STATUS_PERIOD_S), |watchdog| watchdog / 2);
+    let ready_at = Instant::now();
+
+    let mijia_bt = mijia_bt.clone();
+
+    thread::spawn(move || {
+        send(&notifier, &format!("{}\n{}", READY, status(mijia_bt.active_sensors())));
+
+        while mijia_bt.is_listening() {
+            thread::sleep(period);
+
+            send(&notifier, &status(mijia_bt.active_sensors()));
+
+            if let Some(watchdog) = watchdog {
+                let last_activity = mijia_bt.last_activity();
+                if should_ping(last_activity, ready_at, watchdog, Instant::now()) {
+                    send(&notifier, WATCHDOG);
+                } else {
+                    warn!("No bluez advertisement received for {}s, skipping the watchdog ping", last_activity.unwrap_or(ready_at).elapsed().as_secs());
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Receive a notification from a socket.
+    fn receive(socket: &UnixDatagram) -> Vec<u8> {
+        let mut buffer = [0; 256];
+        let length = socket.recv(&mut buffer).unwrap();
+        buffer[..length].to_vec()
+    }
+
+    #[test]
+    fn notifies_a_socket_path() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("notify.sock");
+        let socket = UnixDatagram::bind(&path).unwrap();
+
+        let notifier = Notifier::new(path.to_str().unwrap()).unwrap();
+        notifier.notify(&format!("{}\n{}", READY, status(2))).unwrap();
+
+        assert_eq!(receive(&socket), b"READY=1\nSTATUS=Listening, 2 active sensors");
+    }
+
+    #[test]
+    fn notifies_an_abstract_socket() {
+        let name = format!("mijiabt-test-{}", process::id());
+        let socket = UnixDatagram::bind_addr(&SocketAddr::from_abstract_name(name.as_bytes()).unwrap()).unwrap();
+
+        let notifier = Notifier::new(&format!("@{}", name)).unwrap();
+        notifier.notify(WATCHDOG).unwrap();
+
+        assert_eq!(receive(&socket), b"WATCHDOG=1");
+    }
+
+    #[test]
+    fn reads_the_watchdog_timeout() {
+        assert_eq!(watchdog_timeout(Some("20000000"), None), Some(Duration::from_secs(20)));
+        assert_eq!(watchdog_timeout(Some("20000000"), Some(&process::id().to_string())), Some(Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn ignores_a_disabled_or_invalid_watchdog() {
+        assert_eq!(watchdog_timeout(None, None), None);
+        assert_eq!(watchdog_timeout(Some("0"), None), None);
+        assert_eq!(watchdog_timeout(Some("twenty"), None), None);
+    }
+
+    #[test]
+    fn ignores_the_watchdog_of_another_process() {
+        assert_eq!(watchdog_timeout(Some("20000000"), Some(&(process::id() + 1).to_string())), None);
+        assert_eq!(watchdog_timeout(Some("20000000"), Some("systemd")), None);
+    }
+
+    #[test]
+    fn pings_while_advertisements_are_received() {
+        let ready_at = Instant::now();
+        let watchdog = Duration::from_secs(20);
+
+        assert!(should_ping(Some(ready_at + Duration::from_secs(30)), ready_at, watchdog, ready_at + Duration::from_secs(40)));
+        assert!(!should_ping(Some(ready_at + Duration::from_secs(30)), ready_at, watchdog, ready_at + Duration::from_secs(50)));
+    }
+
+    #[test]
+    fn pings_from_the_ready_time_until_an_advertisement_is_received() {
+        let ready_at = Instant::now();
+        let watchdog = Duration::from_secs(20);
+
+        assert!(should_ping(None, ready_at, watchdog, ready_at + Duration::from_secs(10)));
+        assert!(!should_ping(None, ready_at, watchdog, ready_at + Duration::from_secs(20)));
+    }
+}
@@ -0,0 +1,343 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::LevelFilter;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::symbols::bar;
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+
+use mijiabt::MijiaBt;
+
+use crate::cli::{connect, join_listening, spawn_listening, Options};
+use crate::cli::config::Config;
+
+/// The period the terminal events are polled at, in milliseconds.
+const EVENT_POLL_PERIOD_MS: u64 = 250;
+/// The period the advertisement rate is sampled at for the sparkline, in seconds.
+const RATE_SAMPLE_PERIOD_S: u64 = 5;
+/// The number of advertisement rate samples shown by the sparkline.
+const RATE_SAMPLES: usize = 24;
+/// The time after which a sensor which has not been seen is dimmed, in seconds.
+const STALE_AFTER_S: u64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The column the sensors are sorted by.
+enum SortColumn {
+    Name,
+    Temperature,
+    Humidity,
+    Battery,
+    Rssi,
+    LastSeen,
+    Rate,
+}
+
+impl SortColumn {
+    /// The columns, in display order.
+    const ALL: [SortColumn; 7] = [
+        SortColumn::Name, SortColumn::Temperature, SortColumn::Humidity, SortColumn::Battery,
+        SortColumn::Rssi, SortColumn::LastSeen, SortColumn::Rate
+    ];
+
+    /// Get the next column, wrapping around.
+    fn next(self) -> SortColumn {
+        let index = Self::ALL.iter().position(|column| *column == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Get the title of the column.
+    fn title(self) -> &'static str {
+        match self {
+            SortColumn::Name => "Name",
+            SortColumn::Temperature => "Temp",
+            SortColumn::Humidity => "Humidity",
+            SortColumn::Battery => "Battery",
+            SortColumn::Rssi => "RSSI",
+            SortColumn::LastSeen => "Last seen",
+            SortColumn::Rate => "Adv/min"
+        }
+    }
+}
+
+/// A row of the dashboard: the current state of a sensor.
+struct SensorRow {
+    /// The display name of the sensor.
+    name: String,
+    /// The temperature, in °C.
+    temperature: Option<f32>,
+    /// The relative humidity, in %RH.
+    humidity: Option<f32>,
+    /// The battery level, in percent.
+    battery: Option<u8>,
+    /// The signal strength of the last advertisement, in dBm.
+    rssi: Option<i16>,
+    /// The time elapsed since the last advertisement.
+    last_seen: Duration,
+    /// The number of advertisements received during the last minute.
+    rate: usize,
+    /// The number of advertisements received in each sample period, oldest first.
+    rate_samples: Vec<u64>,
+}
+
+impl SensorRow {
+    /// Compare two rows by a column.
+    fn compare(&self, other: &SensorRow, column: SortColumn) -> Ordering {
+        let ordering = match column {
+            SortColumn::Name => self.name.cmp(&other.name),
+            SortColumn::Temperature => self.temperature.partial_cmp(&other.temperature).unwrap_or(Ordering::Equal),
+            SortColumn::Humidity => self.humidity.partial_cmp(&other.humidity).unwrap_or(Ordering::Equal),
+            SortColumn::Battery => self.battery.cmp(&other.battery),
+            SortColumn::Rssi => self.rssi.cmp(&other.rssi),
+            SortColumn::LastSeen => self.last_seen.cmp(&other.last_seen),
+            SortColumn::Rate => self.rate.cmp(&other.rate)
+        };
+
+        ordering.then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+/// The advertisement rate history of a sensor.
+struct RateHistory {
+    /// The number of advertisements received when the last sample has been taken.
+    last_count: u64,
+    /// The number of advertisements received in each sample period, oldest first.
+    samples: VecDeque<u64>,
+}
+
+/// The state of the dashboard.
+struct Dashboard {
+    /// The mijia bt sensor abstraction.
+    mijia_bt: Arc<MijiaBt>,
+    /// The column the sensors are sorted by.
+    sort_column: SortColumn,
+    /// Whether the sensors are sorted in descending order.
+    descending: bool,
+    /// The advertisement rate histories, by MAC address.
+    rate_histories: HashMap<String, RateHistory>,
+    /// The time the last advertisement rate sample has been taken at.
+    last_sample: Option<Instant>,
+}
+
+impl Dashboard {
+    /// Create a new instance of Dashboard, sorted by name.
+    ///
+    /// # Arguments:
+    /// * `mijia_bt` - The mijia bt sensor abstraction.
+    ///
+    /// Returns a new Dashboard.
+    fn new(mijia_bt: Arc<MijiaBt>) -> Dashboard {
+        Dashboard {
+            mijia_bt,
+            sort_column: SortColumn::Name,
+            descending: false,
+            rate_histories: HashMap::new(),
+            last_sample: None
+        }
+    }
+
+    /// Sample the advertisement rate of every sensor, if the sample period has elapsed.
+    fn sample_rates(&mut self) {
+        if matches!(self.last_sample, Some(last_sample) if last_sample.elapsed() < Duration::from_secs(RATE_SAMPLE_PERIOD_S)) {
+            return;
+        }
+        self.last_sample = Some(Instant::now());
+
+        for (address, statistics) in self.mijia_bt.all_statistics() {
+            let count = statistics.advertisements();
+            let rate_history = self.rate_histories.entry(address).or_insert_with(|| RateHistory {
+                last_count: count,
+                samples: VecDeque::with_capacity(RATE_SAMPLES)
+            });
+
+            if rate_history.samples.len() == RATE_SAMPLES {
+                rate_history.samples.pop_front();
+            }
+            rate_history.samples.push_back(count - rate_history.last_count);
+            rate_history.last_count = count;
+        }
+    }
+
+    /// Build the rows of the sensors, sorted.
+    fn rows(&self) -> Vec<SensorRow> {
+        let snapshots = self.mijia_bt.snapshots();
+
+        let mut rows: Vec<SensorRow> = self.mijia_bt.all_statistics().into_iter()
+            .map(|(address, statistics)| {
+                let snapshot = snapshots.get(&address);
+                let reading = snapshot.map(|snapshot| *snapshot.reading());
+
+                SensorRow {
+                    name: snapshot.map_or_else(|| address.clone(), |snapshot| String::from(snapshot.name())),
+                    temperature: reading.and_then(|reading| reading.temperature).map(|temperature| temperature.celsius()),
+                    humidity: reading.and_then(|reading| reading.humidity).map(|humidity| humidity.percent()),
+                    battery: reading.and_then(|reading| reading.battery).map(|battery| battery.percent()),
+                    rssi: snapshot.and_then(|snapshot| snapshot.metadata().rssi),
+                    last_seen: statistics.last_seen().elapsed(),
+                    rate: statistics.advertisements_per_minute(),
+                    rate_samples: self.rate_histories.get(&address)
+                        .map(|rate_history| rate_history.samples.iter().copied().collect())
+                        .unwrap_or_default()
+                }
+            })
+            .collect();
+
+        rows.sort_by(|a, b| {
+            let ordering = a.compare(b, self.sort_column);
+            if self.descending { ordering.reverse() } else { ordering }
+        });
+
+        rows
+    }
+
+    /// Draw the dashboard.
+    fn draw(&self, frame: &mut Frame) {
+        let [table_area, help_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+        let header = Row::new(SortColumn::ALL.iter().map(|column| {
+            let arrow = match (*column == self.sort_column, self.descending) {
+                (false, _) => "",
+                (true, false) => " ▲",
+                (true, true) => " ▼"
+            };
+            format!("{}{}", column.title(), arrow)
+        }).chain(std::iter::once(String::from("Activity"))))
+            .style(Style::default().add_modifier(Modifier::BOLD));
+
+        let rows = self.rows().into_iter().map(|row| {
+            let style = if row.last_seen.as_secs() >= STALE_AFTER_S {
+                Style::default().add_modifier(Modifier::DIM)
+            } else {
+                Style::default()
+            };
+
+            Row::new(vec![
+                row.name,
+                format_optional(row.temperature.map(|temperature| format!("{:.1} °C", temperature))),
+                format_optional(row.humidity.map(|humidity| format!("{:.1} %", humidity))),
+                format_optional(row.battery.map(|battery| format!("{} %", battery))),
+                format_optional(row.rssi.map(|rssi| format!("{} dBm", rssi))),
+                format_age(row.last_seen),
+                row.rate.to_string(),
+                sparkline(&row.rate_samples)
+            ]).style(style)
+        });
+
+        let widths = [
+            Constraint::Min(17), Constraint::Length(9), Constraint::Length(10), Constraint::Length(9),
+            Constraint::Length(9), Constraint::Length(11), Constraint::Length(9), Constraint::Length(RATE_SAMPLES as u16)
+        ];
+
+        let title = format!(" mijiabt top: {} active sensors ", self.mijia_bt.active_sensors());
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(title));
+
+        frame.render_widget(table, table_area);
+        frame.render_widget(Paragraph::new(" s: sort column  r: reverse order  q: quit"), help_area);
+    }
+
+    /// Handle a key press.
+    ///
+    /// Returns false if the dashboard should be closed, true otherwise.
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char('s') | KeyCode::Right => self.sort_column = self.sort_column.next(),
+            KeyCode::Char('r') => self.descending = !self.descending,
+            _ => {}
+        }
+
+        true
+    }
+}
+
+/// Show a live table of the sensors in the terminal, until the timeout expires or q is pressed.
+/// The logs are disabled while the table is shown, since they would be written over it.
+///
+/// # Arguments:
+/// * `options` - The command line options.
+/// * `config` - The configuration.
+pub fn run(options: &Options, config: &Config) -> Result<(), String> {
+    let mijia_bt = connect(options, config)?;
+    let listening = spawn_listening(&mijia_bt, options.timeout);
+
+    let mut terminal = ratatui::try_init().map_err(|error| format!("Error opening the terminal: {}", error))?;
+    let max_level = log::max_level();
+    log::set_max_level(LevelFilter::Off);
+
+    let result = run_dashboard(&mut terminal, Dashboard::new(mijia_bt.clone()), || listening.is_finished());
+
+    ratatui::restore();
+    log::set_max_level(max_level);
+
+    mijia_bt.stop_listening();
+    result.map_err(|error| format!("Error drawing the dashboard: {}", error))?;
+
+    join_listening(listening)
+}
+
+/// Draw the dashboard and handle the key presses.
+///
+/// # Arguments:
+/// * `terminal` - The terminal.
+/// * `dashboard` - The dashboard.
+/// * `is_finished` - Whether the listening has finished.
+fn run_dashboard(terminal: &mut DefaultTerminal, mut dashboard: Dashboard, is_finished: impl Fn() -> bool) -> io::Result<()> {
+    while !is_finished() {
+        dashboard.sample_rates();
+        terminal.draw(|frame| dashboard.draw(frame))?;
+
+        if event::poll(Duration::from_millis(EVENT_POLL_PERIOD_MS))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !dashboard.handle_key(key.code, key.modifiers) {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Format an optional value, with a dash if it is absent.
+fn format_optional(value: Option<String>) -> String {
+    value.unwrap_or_else(|| String::from("-"))
+}
+
+/// Format the time elapsed since an event, e.g. 42s ago or 3m05s ago.
+fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    if seconds < 60 {
+        format!("{}s ago", seconds)
+    } else if seconds < 3600 {
+        format!("{}m{:02}s ago", seconds / 60, seconds % 60)
+    } else {
+        format!("{}h{:02}m ago", seconds / 3600, seconds / 60 % 60)
+    }
+}
+
+/// Draw a sparkline with block characters, scaled to the maximum sample.
+///
+/// # Arguments:
+/// * `samples` - The samples, oldest first.
+///
+/// Returns the sparkline, one character per sample.
+fn sparkline(samples: &[u64]) -> String {
+    let levels = [
+        bar::NINE_LEVELS.empty, bar::NINE_LEVELS.one_eighth, bar::NINE_LEVELS.one_quarter,
+        bar::NINE_LEVELS.three_eighths, bar::NINE_LEVELS.half, bar::NINE_LEVELS.five_eighths,
+        bar::NINE_LEVELS.three_quarters, bar::NINE_LEVELS.seven_eighths, bar::NINE_LEVELS.full
+    ];
+    let max = samples.iter().copied().max().unwrap_or(0).max(1);
+
+    samples.iter()
+        .map(|sample| levels[(sample * (levels.len() as u64 - 1) / max) as usize])
+        .collect()
+}
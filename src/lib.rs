@@ -1,4 +1,9 @@
-pub use mijia_bt::MijiaBt;
+pub use ble::api::BleAdapter;
+pub use mijia_bt::{ListenError, MijiaBt};
+pub use readings::Readings;
+pub mod advertisement;
 pub mod mijiabt_data;
+pub mod sensor;
 mod ble;
 mod mijia_bt;
+mod readings;
@@ -1,33 +1,274 @@
-use crate::ble::api::BleDevice;
+use crate::advertisement::{Advertisement, MiBeacon};
+use crate::ble::api::{BleAdapter, BleDevice};
 use crate::ble::dbus::dbus_ble_repo::DbusBleRepo;
 
-use crate::mijiabt_data::MijiaBtData;
+use crate::mijiabt_data::{MijiaBtData, Quantity, Reading, ReadingSnapshot, ReceiveMetadata};
+use crate::readings::Readings;
+use crate::sensor::normalize_address;
+use crate::sensor::{Calibration, DeviceFilter, FrameCounterTracker, LinkQuality, ReadingFilter, ReportState, ReportingPolicy};
+use crate::sensor::{SensorHistory, SensorRegistry, SensorStatistics};
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
-const TARGET_DEVICE_NAME: &str = "MJ_HT_V1";
-const TARGET_SERVICE_UUID: &str = "0000fe95-0000-1000-8000-00805f9b34fb";
+use log::{debug, info, trace};
+
+const TARGET_SERVICE_UUID: &str = MiBeacon::SERVICE_UUID;
+const DEFAULT_ADAPTER: &str = "hci0";
+const DEFAULT_SENSOR_LOST_TIMEOUT_S: u64 = 600;
+const DEFAULT_HISTORY_CAPACITY: usize = 4096;
+
+/// A callback taking the MAC address of a sensor as parameter.
+type SensorCallback = Box<dyn FnMut(&str) + Send + Sync + 'static>;
+
+/// A callback taking a raw advertisement as parameter.
+type AdvertisementCallback = Box<dyn FnMut(&Advertisement) + Send + Sync + 'static>;
+
+/// The filters of a sensor, applied in order.
+type SensorFilters = Vec<Box<dyn ReadingFilter>>;
+
+/// A factory building the filters of a sensor, taking the MAC address of the sensor as parameter.
+type FilterFactory = Box<dyn Fn(&str) -> SensorFilters + Send + Sync + 'static>;
+
+/// The sender of a readings iterator, along with the generation of the listening session it belongs to.
+type ReadingSender = (u64, Sender<ReadingSnapshot>);
 
 pub struct MijiaBt {
     ble_repo: DbusBleRepo,
-    current_data: Arc<MijiaBtData>,
+    /// The current data of each sensor, by MAC address.
+    sensors_data: Arc<Mutex<HashMap<String, MijiaBtData>>>,
     on_data_updated: Arc<Mutex<Option<Box<dyn FnMut(&MijiaBtData) + Send + Sync + 'static>>>>,
-    listening: Arc<AtomicBool>
+    /// The senders of the readings iterators.
+    reading_senders: Arc<Mutex<Vec<ReadingSender>>>,
+    on_sensor_appeared: Arc<Mutex<Option<SensorCallback>>>,
+    on_sensor_lost: Arc<Mutex<Option<SensorCallback>>>,
+    /// The last time each sensor has been heard from, by MAC address.
+    sensors_last_seen: Arc<Mutex<HashMap<String, Instant>>>,
+    /// The silence duration after which a sensor is considered lost.
+    sensor_lost_timeout: Duration,
+    /// The filter selecting the sensors to listen.
+    device_filter: Arc<Mutex<DeviceFilter>>,
+    /// The registry of the information about the sensors.
+    sensor_registry: Arc<Mutex<SensorRegistry>>,
+    /// The history of the readings of each sensor, by MAC address.
+    histories: Arc<Mutex<HashMap<String, SensorHistory>>>,
+    /// The maximum number of readings kept in the history of each sensor.
+    history_capacity: Arc<AtomicUsize>,
+    /// The policy deciding which readings are reported.
+    reporting_policy: Arc<Mutex<ReportingPolicy>>,
+    /// The reporting state of each sensor, by MAC address.
+    report_states: Arc<Mutex<HashMap<String, ReportState>>>,
+    /// The frame counter tracker of each sensor, by MAC address.
+    frame_counters: Arc<Mutex<HashMap<String, FrameCounterTracker>>>,
+    /// The factory building the filters of each sensor.
+    filter_factory: Arc<Mutex<FilterFactory>>,
+    /// The filters of each sensor, by MAC address.
+    filters: Arc<Mutex<HashMap<String, SensorFilters>>>,
+    /// The reception statistics of each sensor, by MAC address.
+    statistics: Arc<Mutex<HashMap<String, SensorStatistics>>>,
+    /// The callback called with every advertisement, whatever the device.
+    on_advertisement: Arc<Mutex<Option<AdvertisementCallback>>>,
+    /// The last time an advertisement of any device has been received from bluez.
+    last_activity: Arc<Mutex<Option<Instant>>>,
+    listening: Arc<AtomicBool>,
+    /// The generation of the current listening session, or of the next one if none is running.
+    session: Arc<AtomicU64>
 }
 
 impl MijiaBt {
-    /// Returns a new instance of the mijia_bt sensor abstraction.
+    /// The model of the supported sensors.
+    pub const SUPPORTED_MODEL: &'static str = "MJ_HT_V1";
+    /// The MiBeacon product identifier of the supported sensors.
+    pub const SUPPORTED_PRODUCT_ID: u16 = 0x01AA;
+
+    /// Returns a new instance of the mijia_bt sensor abstraction, listening with the hci0 adapter.
     pub fn new() -> MijiaBt {
-        let mijia_bt = MijiaBt {
-            ble_repo: DbusBleRepo::new(),
-            current_data: Arc::new(MijiaBtData::new(0, 0)),
+        Self::with_adapter(DEFAULT_ADAPTER)
+    }
+
+    /// Returns a new instance of the mijia_bt sensor abstraction.
+    ///
+    /// # Arguments:
+    /// * `adapter` - The name of the bluetooth adapter to listen with, e.g. hci0.
+    pub fn with_adapter(adapter: &str) -> MijiaBt {
+        let mut mijia_bt = MijiaBt {
+            ble_repo: DbusBleRepo::new(adapter),
+            sensors_data: Arc::new(Mutex::new(HashMap::new())),
             on_data_updated: Arc::new(Mutex::new(None)),
-            listening: Arc::new(AtomicBool::new(false))
+            reading_senders: Arc::new(Mutex::new(Vec::new())),
+            on_sensor_appeared: Arc::new(Mutex::new(None)),
+            on_sensor_lost: Arc::new(Mutex::new(None)),
+            sensors_last_seen: Arc::new(Mutex::new(HashMap::new())),
+            sensor_lost_timeout: Duration::from_secs(DEFAULT_SENSOR_LOST_TIMEOUT_S),
+            device_filter: Arc::new(Mutex::new(DeviceFilter::new())),
+            sensor_registry: Arc::new(Mutex::new(SensorRegistry::new())),
+            histories: Arc::new(Mutex::new(HashMap::new())),
+            history_capacity: Arc::new(AtomicUsize::new(DEFAULT_HISTORY_CAPACITY)),
+            reporting_policy: Arc::new(Mutex::new(ReportingPolicy::new())),
+            report_states: Arc::new(Mutex::new(HashMap::new())),
+            frame_counters: Arc::new(Mutex::new(HashMap::new())),
+            filter_factory: Arc::new(Mutex::new(Box::new(|_address| Vec::new()))),
+            filters: Arc::new(Mutex::new(HashMap::new())),
+            statistics: Arc::new(Mutex::new(HashMap::new())),
+            on_advertisement: Arc::new(Mutex::new(None)),
+            last_activity: Arc::new(Mutex::new(None)),
+            listening: Arc::new(AtomicBool::new(false)),
+            session: Arc::new(AtomicU64::new(0))
+        };
+
+        let on_advertisement_data = {
+            let sensors_data_clone = mijia_bt.sensors_data.clone();
+            let on_data_updated_clone = mijia_bt.on_data_updated.clone();
+            let reading_senders_clone = mijia_bt.reading_senders.clone();
+            let on_sensor_appeared_clone = mijia_bt.on_sensor_appeared.clone();
+            let sensors_last_seen_clone = mijia_bt.sensors_last_seen.clone();
+            let device_filter_clone = mijia_bt.device_filter.clone();
+            let sensor_registry_clone = mijia_bt.sensor_registry.clone();
+            let histories_clone = mijia_bt.histories.clone();
+            let history_capacity_clone = mijia_bt.history_capacity.clone();
+            let reporting_policy_clone = mijia_bt.reporting_policy.clone();
+            let report_states_clone = mijia_bt.report_states.clone();
+            let frame_counters_clone = mijia_bt.frame_counters.clone();
+            let filter_factory_clone = mijia_bt.filter_factory.clone();
+            let filters_clone = mijia_bt.filters.clone();
+            let statistics_clone = mijia_bt.statistics.clone();
+            let on_advertisement_clone = mijia_bt.on_advertisement.clone();
+            let last_activity_clone = mijia_bt.last_activity.clone();
+            move |device: &BleDevice| {
+                *last_activity_clone.lock().unwrap() = Some(Instant::now());
+
+                if let Some(on_advertisement) = &mut *on_advertisement_clone.lock().unwrap() {
+                    on_advertisement(&Self::to_advertisement(device));
+                }
+
+                // The sensors are identified by the product id of their MiBeacon frames, since the bluez alias
+                // may have been changed by the user, and the device filter may then match it.
+                let mibeacon = device.service_data.get(TARGET_SERVICE_UUID)
+                    .and_then(|service_data| MiBeacon::parse(service_data))
+                    .filter(|mibeacon| mibeacon.product_id == Self::SUPPORTED_PRODUCT_ID);
+
+                if let Some(mibeacon) = mibeacon.filter(|_| device_filter_clone.lock().unwrap().is_allowed(&device.address, &device.local_name)) {
+                    let appeared = sensors_last_seen_clone.lock().unwrap()
+                        .insert(device.address.clone(), Instant::now())
+                        .is_none();
+
+                    if appeared {
+                        info!(device = device.address.as_str(), adapter = device.adapter.as_str(); "Sensor appeared");
+
+                        if let Some(on_sensor_appeared) = &mut *on_sensor_appeared_clone.lock().unwrap() {
+                            on_sensor_appeared(&device.address);
+                        }
+                    }
+
+                    statistics_clone.lock().unwrap()
+                        .entry(device.address.clone())
+                        .or_insert_with(SensorStatistics::new)
+                        .record_advertisement(device.rssi);
+
+                    // The same advertisement may be delivered several times, e.g. through both
+                    // InterfacesAdded and PropertiesChanged, or repeated by the sensor.
+                    let frame_counter = mibeacon.frame_counter;
+                    let is_new_frame = frame_counters_clone.lock().unwrap()
+                        .entry(device.address.clone())
+                        .or_default()
+                        .track(frame_counter);
+
+                    if !is_new_frame {
+                        trace!(device = device.address.as_str(), frame_counter = frame_counter; "Duplicate frame dropped");
+                        return;
+                    }
+
+                    let decoded_data = DecodedData::from_reading(mibeacon.reading());
+
+                    let frame_type = mibeacon.object.as_ref().map_or(0, |object| object.object_type);
+                    if decoded_data.is_empty() {
+                        debug!(device = device.address.as_str(), adapter = device.adapter.as_str(), frame_type = frame_type; "Undecodable frame");
+                    } else {
+                        debug!(device = device.address.as_str(), adapter = device.adapter.as_str(), frame_type = frame_type; "Frame decoded");
+                    }
+
+                    if let Some(statistics) = statistics_clone.lock().unwrap().get_mut(&device.address) {
+                        statistics.record_frame(!decoded_data.is_empty());
+                    }
+
+                    let mut sensors_data = sensors_data_clone.lock().unwrap();
+                    let mijiabt_data = sensors_data.entry(device.address.clone())
+                        .or_insert_with(|| MijiaBtData::new(&device.address));
+
+                    let sensor_registry = sensor_registry_clone.lock().unwrap();
+                    let calibrated_data = Self::calibrate_mijia_bt_data(&decoded_data, &sensor_registry.calibration(&device.address));
+                    mijiabt_data.set_info(sensor_registry.get(&device.address).cloned());
+                    drop(sensor_registry);
+
+                    let mut filters = filters_clone.lock().unwrap();
+                    let sensor_filters = filters.entry(device.address.clone())
+                        .or_insert_with(|| (filter_factory_clone.lock().unwrap())(&device.address));
+                    let filtered_data = Self::filter_mijia_bt_data(&calibrated_data, sensor_filters);
+                    drop(filters);
+
+                    // Keep the current values of the quantities which are not present in this advertisement.
+                    let (current_raw_temperature, current_raw_humidity) = mijiabt_data.get_raw_tenths();
+                    mijiabt_data.update_raw(
+                        decoded_data.temperature.or(current_raw_temperature),
+                        decoded_data.humidity.or(current_raw_humidity)
+                    );
+
+                    let (current_temperature, current_humidity) = mijiabt_data.get_tenths();
+                    mijiabt_data.update(
+                        filtered_data.temperature.or(current_temperature),
+                        filtered_data.humidity.or(current_humidity)
+                    );
+
+                    if let Some(new_battery) = filtered_data.battery {
+                        mijiabt_data.update_battery(new_battery);
+                    }
+
+                    let metadata = ReceiveMetadata::now(Some(frame_counter), device.rssi, &device.adapter);
+                    let received_instant = metadata.received_instant;
+                    mijiabt_data.set_metadata(metadata);
+
+                    // Every new value is kept in the history, so that its statistics do not depend on the reporting policy.
+                    if !filtered_data.is_empty() {
+                        histories_clone.lock().unwrap()
+                            .entry(device.address.clone())
+                            .or_insert_with(|| SensorHistory::new(history_capacity_clone.load(Ordering::Relaxed)))
+                            .push(mijiabt_data.snapshot());
+                    }
+
+                    let reading = mijiabt_data.get();
+                    let reporting_policy = reporting_policy_clone.lock().unwrap();
+                    let mut report_states = report_states_clone.lock().unwrap();
+                    let report_state = report_states.entry(device.address.clone()).or_default();
+
+                    if report_state.should_report(&reporting_policy, &reading, received_instant) {
+                        report_state.reported(&reading, received_instant);
+                        drop(report_states);
+                        drop(reporting_policy);
+
+                        // Release the sensors data before notifying, so that the callbacks can query it.
+                        let mijiabt_data = mijiabt_data.clone();
+                        drop(sensors_data);
+
+                        if let Some(on_data_updated) = &mut *on_data_updated_clone.lock().unwrap() {
+                            on_data_updated(&mijiabt_data);
+                        }
+
+                        // Drop the senders whose readings iterator has gone away.
+                        reading_senders_clone.lock().unwrap()
+                            .retain(|(_, sender)| sender.send(mijiabt_data.snapshot()).is_ok());
+                    }
+                }
+            }
         };
 
+        mijia_bt.ble_repo.set_on_advertisement_data_callback(on_advertisement_data);
+
         mijia_bt
     }
 
@@ -36,11 +277,21 @@ impl MijiaBt {
     /// # Arguments:
     /// * `timeout` - The time in seconds to listen the bt sensor.
     ///               If None is passed, the program waits forever.
-    pub fn start_listening(&self, timeout: Option<u64>) {
-        println!("Start listening the mijia bt sensor...\n\
-                 Ctrl-C to stop");
+    ///
+    /// Returns an error if the discovery could not be started, e.g. if the adapter does not exist.
+    /// The readings iterators are then closed.
+    pub fn start_listening(&self, timeout: Option<u64>) -> Result<(), ListenError> {
+        info!(adapter = self.ble_repo.adapter(); "Start listening the mijia bt sensors");
+
+        let session = self.session.load(Ordering::SeqCst);
 
-        self.ble_repo.start_scan();
+        if let Err(error) = self.ble_repo.start_scan() {
+            self.close_readings(session);
+            return Err(ListenError {
+                adapter: String::from(self.ble_repo.adapter()),
+                message: error.to_string()
+            });
+        }
         self.listening.store(true, Ordering::SeqCst);
 
         let now = Instant::now();
@@ -48,89 +299,426 @@ impl MijiaBt {
         while self.listening.load(Ordering::SeqCst) {
             thread::sleep(Duration::from_millis(20));
 
+            self.check_lost_sensors();
+
             if let Some(timeout) = timeout {
                 if now.elapsed().as_secs() >= timeout {
                     self.listening.store(false, Ordering::SeqCst);
                 }
             }
         }
+
+        self.close_readings(session);
+
+        Ok(())
     }
 
     /// Stop listening the mijia bt sensor.
     ///
     pub fn stop_listening(&self) {
+        info!(adapter = self.ble_repo.adapter(); "Stop listening the mijia bt sensors");
+
         self.ble_repo.stop_scan();
         self.listening.store(false, Ordering::SeqCst);
     }
 
+    /// Check whether the mijia bt sensor is being listened, i.e. the discovery has been started.
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of active sensors, i.e. the sensors which appeared and are not lost.
+    pub fn active_sensors(&self) -> usize {
+        self.sensors_last_seen.lock().unwrap().len()
+    }
+
+    /// Get the last time an advertisement of any bluetooth device has been received from bluez,
+    /// to check that the bluez events are still flowing.
+    ///
+    /// Returns the time of the last advertisement, or None if none has been received yet.
+    pub fn last_activity(&self) -> Option<Instant> {
+        *self.last_activity.lock().unwrap()
+    }
+
+    /// Get a blocking iterator over the mijia bt readings.
+    ///
+    /// The iterator yields a snapshot of each decoded reading in order.
+    /// It ends once `stop_listening` is called or the `start_listening` timeout expires.
+    /// Created during a listening session, it yields the readings of this session.
+    /// Created between two listening sessions, it yields the readings of the next one.
+    pub fn readings(&self) -> Readings {
+        let (sender, receiver) = mpsc::channel();
+
+        let mut reading_senders = self.reading_senders.lock().unwrap();
+        reading_senders.push((self.session.load(Ordering::SeqCst), sender));
+
+        Readings::new(receiver)
+    }
+
     /// Set the on data updated callback.
     ///
     /// # Arguments:
-    /// * `callback` - The callback to call when a mijia bt data update occurs
-    ///                The callback take a (temperature, humidity) u16 tuple as parameter.
+    /// * `callback` - The callback to call when a mijia bt data update is reported, see `set_reporting_policy`.
+    ///   The callback take a reference on the data of the updated sensor as parameter.
     pub fn set_on_data_updated_callback(&mut self, callback: Option<impl FnMut(&MijiaBtData) + Send + Sync + 'static>) {
-        match callback {
-            None => self.on_data_updated = Arc::new(Mutex::new(None)),
-            Some(callback) => {
-                self.on_data_updated = Arc::new(Mutex::new(Some(Box::new(callback))));
-
-                let on_advertisement_data = {
-                    let mijiabt_data_clone = self.current_data.clone();
-                    let on_data_updated_clone = self.on_data_updated.clone();
-                    move |device: &BleDevice| {
-                        if device.local_name == TARGET_DEVICE_NAME {
-                            if device.service_data.contains_key(TARGET_SERVICE_UUID) {
-                                let new_data = Self::parse_mijia_bt_data(&device.service_data[TARGET_SERVICE_UUID]);
-
-                                let mut data_changed = false;
-
-                                let (current_temperature, current_humidity) = mijiabt_data_clone.get();
-                                if current_temperature != new_data.0 && new_data.0 != 0 {
-                                    data_changed = true;
-                                }
-
-                                if current_humidity != new_data.1  && new_data.1 != 0 {
-                                    data_changed = true;
-                                }
-
-                                if data_changed {
-                                    mijiabt_data_clone.update(new_data.0, new_data.1);
-
-                                    if let Some(on_data_updated) = &mut *on_data_updated_clone.lock().unwrap() {
-                                        on_data_updated(&*mijiabt_data_clone);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                };
+        *self.on_data_updated.lock().unwrap() = match callback {
+            None => None,
+            Some(callback) => Some(Box::new(callback))
+        };
+    }
 
-                self.ble_repo.set_on_advertisement_data_callback(on_advertisement_data);
-            }
+    /// Set the on advertisement callback, to inspect what the devices broadcast.
+    ///
+    /// # Arguments:
+    /// * `callback` - The callback to call with every advertisement received while listening,
+    ///   whatever the device, before the model and the device filter are checked.
+    pub fn set_on_advertisement_callback(&self, callback: Option<impl FnMut(&Advertisement) + Send + Sync + 'static>) {
+        *self.on_advertisement.lock().unwrap() = match callback {
+            None => None,
+            Some(callback) => Some(Box::new(callback))
+        };
+    }
+
+    /// Set the on sensor appeared callback.
+    ///
+    /// The callback is called when a mijia bt sensor is heard from for the first time,
+    /// or again after having been lost.
+    ///
+    /// # Arguments:
+    /// * `callback` - The callback, taking the MAC address of the sensor as parameter.
+    pub fn set_on_sensor_appeared_callback(&mut self, callback: Option<impl FnMut(&str) + Send + Sync + 'static>) {
+        *self.on_sensor_appeared.lock().unwrap() = match callback {
+            None => None,
+            Some(callback) => Some(Box::new(callback))
+        };
+    }
+
+    /// Set the on sensor lost callback.
+    ///
+    /// The callback is called when a mijia bt sensor has not been heard from for the sensor lost timeout.
+    ///
+    /// # Arguments:
+    /// * `callback` - The callback, taking the MAC address of the sensor as parameter.
+    pub fn set_on_sensor_lost_callback(&mut self, callback: Option<impl FnMut(&str) + Send + Sync + 'static>) {
+        *self.on_sensor_lost.lock().unwrap() = match callback {
+            None => None,
+            Some(callback) => Some(Box::new(callback))
+        };
+    }
+
+    /// Set the sensor lost timeout.
+    ///
+    /// # Arguments:
+    /// * `timeout` - The silence duration after which a sensor is considered lost. Defaults to 10 minutes.
+    pub fn set_sensor_lost_timeout(&mut self, timeout: Duration) {
+        self.sensor_lost_timeout = timeout;
+    }
+
+    /// Set the device filter.
+    /// Only the sensors allowed by the filter are decoded and reported.
+    ///
+    /// # Arguments:
+    /// * `device_filter` - The filter selecting the sensors to listen.
+    pub fn set_device_filter(&self, device_filter: DeviceFilter) {
+        *self.device_filter.lock().unwrap() = device_filter;
+    }
+
+    /// Set the sensor registry.
+    /// The information it holds about a sensor is attached to each of its readings,
+    /// and its calibration is applied to the decoded values.
+    ///
+    /// # Arguments:
+    /// * `sensor_registry` - The registry of the information about the sensors.
+    pub fn set_sensor_registry(&self, sensor_registry: SensorRegistry) {
+        *self.sensor_registry.lock().unwrap() = sensor_registry;
+    }
+
+    /// Set the reporting policy.
+    /// The readings which are not reported are still kept as the current sensor data,
+    /// and in the history, but do not reach the callback and the readings iterators.
+    ///
+    /// # Arguments:
+    /// * `reporting_policy` - The policy deciding which readings are reported. Defaults to any change.
+    pub fn set_reporting_policy(&self, reporting_policy: ReportingPolicy) {
+        *self.reporting_policy.lock().unwrap() = reporting_policy;
+    }
+
+    /// Set the filters applied to the calibrated values before they are reported.
+    /// The filters of every sensor are rebuilt, dropping their state.
+    /// The factory takes the MAC address of a sensor as parameter, so that filters can be configured per sensor.
+    ///
+    /// # Arguments:
+    /// * `filter_factory` - The factory building the filters of a sensor, applied in order.
+    pub fn set_filters(&self, filter_factory: impl Fn(&str) -> SensorFilters + Send + Sync + 'static) {
+        *self.filter_factory.lock().unwrap() = Box::new(filter_factory);
+        self.filters.lock().unwrap().clear();
+    }
+
+    /// Set the maximum number of readings kept in the history of each sensor.
+    /// It applies to the sensors heard from for the first time afterwards.
+    ///
+    /// # Arguments:
+    /// * `capacity` - The maximum number of readings. Defaults to 4096.
+    pub fn set_history_capacity(&self, capacity: usize) {
+        self.history_capacity.store(capacity, Ordering::Relaxed);
+    }
+
+    /// Get the history of the readings of a sensor.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    ///
+    /// Returns a copy of the history, or None if no reading of the sensor has been received yet.
+    pub fn history(&self, address: &str) -> Option<SensorHistory> {
+        self.histories.lock().unwrap().get(&normalize_address(address)).cloned()
+    }
+
+    /// Get the link quality of a sensor, measured from the MiBeacon frame counters.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    ///
+    /// Returns the number of received, duplicate and lost frames, or None if no frame of the sensor has been received yet.
+    pub fn link_quality(&self, address: &str) -> Option<LinkQuality> {
+        self.frame_counters.lock().unwrap()
+            .get(&normalize_address(address))
+            .map(|frame_counter_tracker| frame_counter_tracker.link_quality())
+    }
+
+    /// Get the reception statistics of a sensor.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    ///
+    /// Returns a copy of the statistics, or None if no advertisement of the sensor has been received yet.
+    pub fn statistics(&self, address: &str) -> Option<SensorStatistics> {
+        let address = normalize_address(address);
+
+        let mut statistics = self.statistics.lock().unwrap().get(&address).cloned()?;
+        if let Some(link_quality) = self.link_quality(&address) {
+            statistics.set_link_quality(link_quality);
         }
+
+        Some(statistics)
     }
 
-    /// Parse mijia btd advertisement data into temperature and humidity.
+    /// Get the reception statistics of every sensor.
+    ///
+    /// Returns a copy of the statistics, by MAC address.
+    pub fn all_statistics(&self) -> BTreeMap<String, SensorStatistics> {
+        let addresses: Vec<String> = self.statistics.lock().unwrap().keys().cloned().collect();
+
+        addresses.into_iter()
+            .filter_map(|address| self.statistics(&address).map(|statistics| (address, statistics)))
+            .collect()
+    }
+
+    /// Get the current reading of every sensor, whether it has been reported or not.
+    ///
+    /// Returns a snapshot of the current reading of each sensor a frame has been received from, by MAC address.
+    pub fn snapshots(&self) -> BTreeMap<String, ReadingSnapshot> {
+        self.sensors_data.lock().unwrap().iter()
+            .map(|(address, mijiabt_data)| (address.clone(), mijiabt_data.snapshot()))
+            .collect()
+    }
+
+    /// List the bluetooth adapters known by bluez.
+    ///
+    /// Returns the adapters, sorted by name, or an error if bluez cannot be reached, e.g. if there is no system bus.
+    pub fn adapters() -> Result<Vec<BleAdapter>, dbus::Error> {
+        DbusBleRepo::adapters()
+    }
+
+    /// Decode mijia bt advertisement service data, without calibration nor filtering.
     ///
     /// # Arguments:
-    /// * `input` - The input raw data.
+    /// * `service_data` - The service data advertised for the fe95 service UUID.
     ///
-    /// Returns a (temperature, humidity) u16 tuple.
-    fn parse_mijia_bt_data(input: &Vec<u8>) -> (u16, u16) {
-        let mut temperature: u16 = 0;
-        let mut humidity : u16 = 0;
-        if input.len() == 18 {
-            temperature = ((input[15] as u16) << 8) | input[14] as u16;
-            humidity = ((input[17] as u16) << 8) | input[16] as u16;
-        } else if input.len() == 16 {
-            if input[11] == 6 {
-                humidity = ((input[15] as u16) << 8) | input[14] as u16;
-            } else if input[11] == 4 {
-                temperature = ((input[15] as u16) << 8) | input[14] as u16;
+    /// Returns the decoded reading. The values which are not present in the service data are None.
+    pub fn decode(service_data: &[u8]) -> Reading {
+        MiBeacon::parse(service_data)
+            .and_then(|mibeacon| mibeacon.reading())
+            .unwrap_or(Reading {
+                temperature: None,
+                humidity: None,
+                battery: None,
+                voltage: None
+            })
+    }
+
+    /// Forget the sensors which have been silent for longer than the sensor lost timeout,
+    /// and notify the on sensor lost callback about them.
+    fn check_lost_sensors(&self) {
+        let mut lost_sensors = Vec::new();
+
+        self.sensors_last_seen.lock().unwrap().retain(|address, last_seen| {
+            let lost = last_seen.elapsed() >= self.sensor_lost_timeout;
+            if lost {
+                lost_sensors.push(address.clone());
+            }
+            !lost
+        });
+
+        // A sensor coming back may have rebooted, so its next frame counter is not compared with the last one.
+        let mut frame_counters = self.frame_counters.lock().unwrap();
+        for address in &lost_sensors {
+            info!(device = address.as_str(); "Sensor lost");
+
+            if let Some(frame_counter_tracker) = frame_counters.get_mut(address) {
+                frame_counter_tracker.reset();
+            }
+        }
+        drop(frame_counters);
+
+        if let Some(on_sensor_lost) = &mut *self.on_sensor_lost.lock().unwrap() {
+            for address in &lost_sensors {
+                on_sensor_lost(address);
             }
         }
+    }
+
+    /// Close the readings iterators of a listening session which has ended.
+    /// The iterators handed out from now on belong to the next session.
+    ///
+    /// # Arguments:
+    /// * `session` - The generation of the session.
+    fn close_readings(&self, session: u64) {
+        let mut reading_senders = self.reading_senders.lock().unwrap();
+        self.session.store(session + 1, Ordering::SeqCst);
+        reading_senders.retain(|(sender_session, _)| *sender_session > session);
+    }
+
+    /// Convert a ble device abstraction to a raw advertisement.
+    ///
+    /// # Arguments:
+    /// * `device` - The ble device, holding its last advertisement.
+    ///
+    /// Returns the raw advertisement.
+    fn to_advertisement(device: &BleDevice) -> Advertisement {
+        Advertisement {
+            path: device.path.clone(),
+            address: device.address.clone(),
+            alias: device.local_name.clone(),
+            adapter: device.adapter.clone(),
+            rssi: device.rssi,
+            service_data: device.service_data.iter().map(|(uuid, data)| (uuid.clone(), data.clone())).collect(),
+            manufacturer_data: device.manufacturer_data.iter().map(|(company_id, data)| (*company_id, data.clone())).collect(),
+            received_at: SystemTime::now()
+        }
+    }
+
+    /// Apply a calibration to parsed mijia bt data.
+    ///
+    /// # Arguments:
+    /// * `input` - The parsed data.
+    /// * `calibration` - The calibration of the sensor.
+    ///
+    /// Returns the calibrated data. The values which are not present are left to None.
+    fn calibrate_mijia_bt_data(input: &DecodedData, calibration: &Calibration) -> DecodedData {
+        let temperature = input.temperature.map(|temperature| {
+            let celsius = calibration.temperature.apply(f32::from(temperature) / 10.0);
+            Self::to_temperature_tenths(celsius)
+        });
+
+        let humidity = input.humidity.map(|humidity| {
+            let percent = calibration.humidity.apply(f32::from(humidity) / 10.0);
+            (percent * 10.0).round().clamp(0.0, 1000.0) as u16
+        });
+
+        DecodedData {
+            temperature,
+            humidity,
+            battery: input.battery
+        }
+    }
+
+    /// Convert a temperature to tenths of °C, as stored.
+    ///
+    /// # Arguments:
+    /// * `celsius` - The temperature, in °C.
+    ///
+    /// Returns the temperature in tenths of °C, clamped above i16::MIN which stands for an unknown temperature,
+    /// so that an extreme calibration does not turn a reading into a missing one.
+    fn to_temperature_tenths(celsius: f32) -> i16 {
+        (celsius * 10.0).round().clamp(f32::from(i16::MIN + 1), f32::from(i16::MAX)) as i16
+    }
+
+    /// Apply the filters of a sensor to its calibrated data.
+    ///
+    /// # Arguments:
+    /// * `input` - The calibrated data.
+    /// * `filters` - The filters of the sensor, applied in order.
+    ///
+    /// Returns the filtered data. The values which are not present or have been rejected are None.
+    fn filter_mijia_bt_data(input: &DecodedData, filters: &mut [Box<dyn ReadingFilter>]) -> DecodedData {
+        let mut filter = |quantity: Quantity, value: f32| {
+            filters.iter_mut().try_fold(value, |value, filter| filter.filter(quantity, value))
+        };
+
+        let temperature = input.temperature
+            .and_then(|temperature| filter(Quantity::Temperature, f32::from(temperature) / 10.0))
+            .map(Self::to_temperature_tenths);
+
+        let humidity = input.humidity
+            .and_then(|humidity| filter(Quantity::Humidity, f32::from(humidity) / 10.0))
+            .map(|percent| (percent * 10.0).round().clamp(0.0, 1000.0) as u16);
+
+        let battery = input.battery
+            .and_then(|battery| filter(Quantity::Battery, f32::from(battery)))
+            .map(|percent| percent.round().clamp(0.0, 100.0) as u8);
+
+        DecodedData {
+            temperature,
+            humidity,
+            battery
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// An error while starting to listen the mijia bt sensors.
+pub struct ListenError {
+    /// The name of the adapter, e.g. hci0.
+    adapter: String,
+    /// The description of the error, as reported by bluez.
+    message: String,
+}
+
+impl fmt::Display for ListenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Error starting the discovery on {}: {}", self.adapter, self.message)
+    }
+}
+
+impl Error for ListenError {}
+
+/// The values decoded from a mijia bt advertisement.
+/// The values which are not present in the advertisement are None.
+struct DecodedData {
+    /// The temperature, in tenths of °C.
+    temperature: Option<i16>,
+    /// The humidity, in tenths of %RH.
+    humidity: Option<u16>,
+    /// The battery level, in percent.
+    battery: Option<u8>,
+}
+
+impl DecodedData {
+    /// Create a new instance of DecodedData from the reading of a MiBeacon frame.
+    ///
+    /// # Arguments:
+    /// * `reading` - The reading of the frame, or None if the frame carries no known value.
+    fn from_reading(reading: Option<Reading>) -> DecodedData {
+        DecodedData {
+            temperature: reading.and_then(|reading| reading.temperature).map(|temperature| MijiaBt::to_temperature_tenths(temperature.celsius())),
+            humidity: reading.and_then(|reading| reading.humidity).map(|humidity| (humidity.percent() * 10.0).round() as u16),
+            battery: reading.and_then(|reading| reading.battery).map(|battery| battery.percent())
+        }
+    }
 
-        (temperature, humidity)
+    /// Check whether no value has been decoded.
+    fn is_empty(&self) -> bool {
+        self.temperature.is_none() && self.humidity.is_none() && self.battery.is_none()
     }
 }
 
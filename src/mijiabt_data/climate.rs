@@ -0,0 +1,127 @@
+use serde::{Serialize, Deserialize};
+
+use crate::mijiabt_data::{Humidity, Temperature};
+
+/// The Magnus formula coefficients over water (Sonntag 1990), for the -45 °C to 60 °C range.
+const MAGNUS_A_HPA: f32 = 6.112;
+const MAGNUS_B: f32 = 17.62;
+const MAGNUS_C_CELSIUS: f32 = 243.12;
+
+/// The specific gas constant of water vapour divided by 100, to get g/m³ from hPa and K.
+const ABSOLUTE_HUMIDITY_FACTOR: f32 = 216.7;
+const ZERO_CELSIUS_KELVIN: f32 = 273.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// The climate metrics derived from a temperature and a relative humidity.
+pub struct ClimateMetrics {
+    /// The dew point, below which water condenses.
+    pub dew_point: Option<Temperature>,
+    /// The absolute humidity, in g/m³.
+    pub absolute_humidity: Option<f32>,
+    /// The heat index, the temperature felt by the human body (NOAA).
+    pub heat_index: Option<Temperature>,
+    /// The humidex, the temperature felt by the human body (Environment Canada).
+    pub humidex: Option<f32>,
+    /// The vapour pressure deficit, in kPa.
+    pub vapour_pressure_deficit: Option<f32>,
+}
+
+impl ClimateMetrics {
+    /// Compute the climate metrics.
+    ///
+    /// # Arguments:
+    /// * `temperature` - The temperature.
+    /// * `humidity` - The relative humidity.
+    ///
+    /// Returns the climate metrics. The dew point and the humidex are not defined for a zero humidity.
+    pub fn compute(temperature: Temperature, humidity: Humidity) -> ClimateMetrics {
+        ClimateMetrics {
+            dew_point: dew_point(temperature, humidity),
+            absolute_humidity: Some(absolute_humidity(temperature, humidity)),
+            heat_index: Some(heat_index(temperature, humidity)),
+            humidex: humidex(temperature, humidity),
+            vapour_pressure_deficit: Some(vapour_pressure_deficit(temperature, humidity))
+        }
+    }
+}
+
+/// Compute the saturation vapour pressure with the Magnus formula.
+///
+/// Returns the saturation vapour pressure, in hPa.
+fn saturation_vapour_pressure(temperature: Temperature) -> f32 {
+    let celsius = temperature.celsius();
+
+    MAGNUS_A_HPA * (MAGNUS_B * celsius / (MAGNUS_C_CELSIUS + celsius)).exp()
+}
+
+/// Compute the actual vapour pressure.
+///
+/// Returns the vapour pressure, in hPa.
+fn vapour_pressure(temperature: Temperature, humidity: Humidity) -> f32 {
+    humidity.percent() / 100.0 * saturation_vapour_pressure(temperature)
+}
+
+/// Compute the dew point by inverting the Magnus formula.
+///
+/// Returns the dew point, or None for a zero humidity.
+pub(crate) fn dew_point(temperature: Temperature, humidity: Humidity) -> Option<Temperature> {
+    if humidity.percent() <= 0.0 {
+        return None;
+    }
+
+    let gamma = (vapour_pressure(temperature, humidity) / MAGNUS_A_HPA).ln();
+
+    Some(Temperature::from_celsius(MAGNUS_C_CELSIUS * gamma / (MAGNUS_B - gamma)))
+}
+
+/// Compute the absolute humidity.
+///
+/// Returns the absolute humidity, in g/m³.
+pub(crate) fn absolute_humidity(temperature: Temperature, humidity: Humidity) -> f32 {
+    ABSOLUTE_HUMIDITY_FACTOR * vapour_pressure(temperature, humidity) / (temperature.celsius() + ZERO_CELSIUS_KELVIN)
+}
+
+/// Compute the heat index with the NOAA algorithm: the Steadman simple formula,
+/// refined by the Rothfusz regression and its adjustments above 80 °F.
+///
+/// Returns the heat index.
+pub(crate) fn heat_index(temperature: Temperature, humidity: Humidity) -> Temperature {
+    let t = temperature.fahrenheit();
+    let rh = humidity.percent();
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return Temperature::from_fahrenheit(simple);
+    }
+
+    let mut heat_index = -42.379 + 2.049_015_2 * t + 10.143_331 * rh
+        - 0.224_755_4 * t * rh - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh - 0.000_001_99 * t * t * rh * rh;
+
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        heat_index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        heat_index += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+    }
+
+    Temperature::from_fahrenheit(heat_index)
+}
+
+/// Compute the humidex from the dew point.
+///
+/// Returns the humidex, or None for a zero humidity.
+pub(crate) fn humidex(temperature: Temperature, humidity: Humidity) -> Option<f32> {
+    let dew_point = dew_point(temperature, humidity)?;
+    let dew_point_kelvin = dew_point.celsius() + ZERO_CELSIUS_KELVIN;
+    let vapour_pressure = 6.11 * (5417.753 * (1.0 / 273.16 - 1.0 / dew_point_kelvin)).exp();
+
+    Some(temperature.celsius() + 0.5555 * (vapour_pressure - 10.0))
+}
+
+/// Compute the vapour pressure deficit, the difference between the saturation and the actual vapour pressure.
+///
+/// Returns the vapour pressure deficit, in kPa.
+pub(crate) fn vapour_pressure_deficit(temperature: Temperature, humidity: Humidity) -> f32 {
+    (saturation_vapour_pressure(temperature) - vapour_pressure(temperature, humidity)) / 10.0
+}
@@ -1,45 +1,217 @@
-use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::atomic::{AtomicI16, AtomicU8, AtomicU16, Ordering};
 
-use serde::{Serialize, Deserialize};
+use crate::mijiabt_data::{BatteryLevel, Humidity, Reading, ReadingSnapshot, ReceiveMetadata, Temperature};
+use crate::sensor::SensorInfo;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The stored values when the sensor did not send them yet.
+/// They are out of the range of the values the sensor sends, unlike 0 which is a genuine value.
+const UNKNOWN_TEMPERATURE: i16 = i16::MIN;
+const UNKNOWN_HUMIDITY: u16 = u16::MAX;
+const UNKNOWN_BATTERY_LEVEL: u8 = u8::MAX;
+
+#[derive(Debug)]
 /// An abstraction of the mijiabt sensor data.
 pub struct MijiaBtData {
+    /// The MAC address of the thermometer.
+    address: String,
+    /// The registered information about the thermometer, if any.
+    info: Option<SensorInfo>,
     /// The temperature of the thermometer.
-    temperature: AtomicU16,
+    temperature: AtomicI16,
     /// The humidity of the thermometer.
     humidity: AtomicU16,
+    /// The temperature of the thermometer, before calibration.
+    raw_temperature: AtomicI16,
+    /// The humidity of the thermometer, before calibration.
+    raw_humidity: AtomicU16,
+    /// The battery level of the thermometer.
+    battery: AtomicU8,
+    /// The reception metadata of the last advertisement of the thermometer.
+    metadata: ReceiveMetadata,
 }
 
 impl MijiaBtData {
     /// Create a new instance of MijiaBtData.
     ///
-    /// Returns a new instance of MijiaBtData.
-    pub fn new(temperature: u16, humidity: u16) -> MijiaBtData {
+    /// # Arguments:
+    /// * `address` - The MAC address of the thermometer.
+    ///
+    /// Returns a new instance of MijiaBtData, with no value received yet.
+    pub fn new(address: &str) -> MijiaBtData {
         MijiaBtData {
-            temperature: AtomicU16::new(temperature),
-            humidity: AtomicU16::new(humidity)
+            address: String::from(address),
+            info: None,
+            temperature: AtomicI16::new(UNKNOWN_TEMPERATURE),
+            humidity: AtomicU16::new(UNKNOWN_HUMIDITY),
+            raw_temperature: AtomicI16::new(UNKNOWN_TEMPERATURE),
+            raw_humidity: AtomicU16::new(UNKNOWN_HUMIDITY),
+            battery: AtomicU8::new(UNKNOWN_BATTERY_LEVEL),
+            metadata: ReceiveMetadata::now(None, None, "")
         }
     }
 
     /// Update MijiaBtData with new values.
     ///
     /// # Arguments:
-    /// * `temperature` - The new temperature.
-    /// * `humidity` - The new humidity.
-    pub fn update(&self, temperature: u16, humidity: u16) {
-        self.temperature.store(temperature, Ordering::Relaxed);
-        self.humidity.store(humidity, Ordering::Relaxed);
+    /// * `temperature` - The new temperature, in tenths of °C, or None if unknown.
+    /// * `humidity` - The new humidity, in tenths of %RH, or None if unknown.
+    pub fn update(&self, temperature: Option<i16>, humidity: Option<u16>) {
+        self.temperature.store(temperature.unwrap_or(UNKNOWN_TEMPERATURE), Ordering::Relaxed);
+        self.humidity.store(humidity.unwrap_or(UNKNOWN_HUMIDITY), Ordering::Relaxed);
+    }
+
+    /// Update the battery level of MijiaBtData.
+    ///
+    /// # Arguments:
+    /// * `battery` - The new battery level, in percent.
+    pub fn update_battery(&self, battery: u8) {
+        self.battery.store(battery, Ordering::Relaxed);
     }
 
     /// Get the current value of the MijiaBtData.
     ///
-    /// Returns a tuple containing the temperature and the humidity as a (u16, u16).
-    /// The values have to be divided by 10 to obtain the right values.
-    pub fn get(&self) -> (u16, u16) {
-        (
+    /// Returns the current reading.
+    pub fn get(&self) -> Reading {
+        self.reading(
             self.temperature.load(Ordering::Relaxed),
             self.humidity.load(Ordering::Relaxed)
         )
     }
+
+    /// Get the current value of the MijiaBtData, as stored.
+    ///
+    /// Returns a tuple containing the temperature and the humidity, in tenths, or None if unknown.
+    pub(crate) fn get_tenths(&self) -> (Option<i16>, Option<u16>) {
+        (
+            Self::known(self.temperature.load(Ordering::Relaxed), UNKNOWN_TEMPERATURE),
+            Self::known(self.humidity.load(Ordering::Relaxed), UNKNOWN_HUMIDITY)
+        )
+    }
+
+    /// Update the values of MijiaBtData before calibration.
+    ///
+    /// # Arguments:
+    /// * `temperature` - The new raw temperature, in tenths of °C, or None if unknown.
+    /// * `humidity` - The new raw humidity, in tenths of %RH, or None if unknown.
+    pub fn update_raw(&self, temperature: Option<i16>, humidity: Option<u16>) {
+        self.raw_temperature.store(temperature.unwrap_or(UNKNOWN_TEMPERATURE), Ordering::Relaxed);
+        self.raw_humidity.store(humidity.unwrap_or(UNKNOWN_HUMIDITY), Ordering::Relaxed);
+    }
+
+    /// Get the value of the MijiaBtData before calibration, for auditing purposes.
+    ///
+    /// Returns the current reading, with the raw temperature and humidity.
+    pub fn get_raw(&self) -> Reading {
+        self.reading(
+            self.raw_temperature.load(Ordering::Relaxed),
+            self.raw_humidity.load(Ordering::Relaxed)
+        )
+    }
+
+    /// Get the value of the MijiaBtData before calibration, as sent by the sensor.
+    ///
+    /// Returns a tuple containing the raw temperature and the raw humidity, in tenths, or None if unknown.
+    pub(crate) fn get_raw_tenths(&self) -> (Option<i16>, Option<u16>) {
+        (
+            Self::known(self.raw_temperature.load(Ordering::Relaxed), UNKNOWN_TEMPERATURE),
+            Self::known(self.raw_humidity.load(Ordering::Relaxed), UNKNOWN_HUMIDITY)
+        )
+    }
+
+    /// Get the MAC address of the thermometer.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Get the registered information about the thermometer.
+    ///
+    /// Returns the information, or None if the thermometer is not registered.
+    pub fn info(&self) -> Option<&SensorInfo> {
+        self.info.as_ref()
+    }
+
+    /// Set the registered information about the thermometer.
+    ///
+    /// # Arguments:
+    /// * `info` - The information, or None if the thermometer is not registered.
+    pub fn set_info(&mut self, info: Option<SensorInfo>) {
+        self.info = info;
+    }
+
+    /// Get the reception metadata of the last advertisement of the thermometer.
+    pub fn metadata(&self) -> &ReceiveMetadata {
+        &self.metadata
+    }
+
+    /// Set the reception metadata of the last advertisement of the thermometer.
+    ///
+    /// # Arguments:
+    /// * `metadata` - The reception metadata.
+    pub fn set_metadata(&mut self, metadata: ReceiveMetadata) {
+        self.metadata = metadata;
+    }
+
+    /// Take an immutable snapshot of the MijiaBtData.
+    ///
+    /// Returns the current reading along with the sensor identity and the reception metadata.
+    pub fn snapshot(&self) -> ReadingSnapshot {
+        ReadingSnapshot::new(&self.address, self.info.clone(), self.get(), self.get_raw(), self.metadata.clone())
+    }
+
+    /// Get the display name of the thermometer.
+    ///
+    /// Returns the registered name of the thermometer, or its MAC address if it is not registered.
+    pub fn name(&self) -> &str {
+        match &self.info {
+            None => &self.address,
+            Some(info) => &info.name
+        }
+    }
+
+    /// Build a reading from stored temperature and humidity tenths, and the current battery level.
+    ///
+    /// # Arguments:
+    /// * `temperature` - The stored temperature, in tenths of °C.
+    /// * `humidity` - The stored humidity, in tenths of %RH.
+    ///
+    /// Returns the reading.
+    fn reading(&self, temperature: i16, humidity: u16) -> Reading {
+        Reading {
+            temperature: Self::known(temperature, UNKNOWN_TEMPERATURE).map(Temperature::from_tenths),
+            humidity: Self::known(humidity, UNKNOWN_HUMIDITY).map(Humidity::from_tenths),
+            battery: Self::known(self.battery.load(Ordering::Relaxed), UNKNOWN_BATTERY_LEVEL).map(BatteryLevel::from_percent),
+            voltage: None
+        }
+    }
+
+    /// Convert a stored value to an option.
+    ///
+    /// # Arguments:
+    /// * `value` - The stored value.
+    /// * `unknown` - The stored value meaning that the value is unknown.
+    ///
+    /// Returns the value, or None if it is unknown.
+    fn known<T: PartialEq>(value: T, unknown: T) -> Option<T> {
+        if value == unknown {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+impl Clone for MijiaBtData {
+    fn clone(&self) -> MijiaBtData {
+        let mut mijiabt_data = MijiaBtData::new(&self.address);
+
+        mijiabt_data.temperature.store(self.temperature.load(Ordering::Relaxed), Ordering::Relaxed);
+        mijiabt_data.humidity.store(self.humidity.load(Ordering::Relaxed), Ordering::Relaxed);
+        mijiabt_data.raw_temperature.store(self.raw_temperature.load(Ordering::Relaxed), Ordering::Relaxed);
+        mijiabt_data.raw_humidity.store(self.raw_humidity.load(Ordering::Relaxed), Ordering::Relaxed);
+        mijiabt_data.battery.store(self.battery.load(Ordering::Relaxed), Ordering::Relaxed);
+        mijiabt_data.set_info(self.info.clone());
+        mijiabt_data.set_metadata(self.metadata.clone());
+
+        mijiabt_data
+    }
 }
@@ -1,3 +1,11 @@
+mod climate;
 mod mijiabt_data;
+mod reading;
+mod reading_event;
+mod reading_snapshot;
 
+pub use climate::ClimateMetrics;
 pub use mijiabt_data::MijiaBtData;
+pub use reading::{BatteryLevel, Humidity, Quantity, Reading, Temperature, Voltage};
+pub use reading_event::{ClimateQuantities, Measurement, Quantities, ReadingEvent, ReadingEventError};
+pub use reading_snapshot::{ReadingSnapshot, ReceiveMetadata};
@@ -0,0 +1,242 @@
+use std::fmt;
+
+use serde::{Serialize, Deserialize};
+
+use crate::mijiabt_data::climate::{self, ClimateMetrics};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// A temperature.
+pub struct Temperature {
+    /// The temperature in degrees Celsius.
+    celsius: f32,
+}
+
+impl Temperature {
+    /// Create a new temperature from degrees Celsius.
+    pub fn from_celsius(celsius: f32) -> Temperature {
+        Temperature {
+            celsius
+        }
+    }
+
+    /// Create a new temperature from degrees Fahrenheit.
+    pub fn from_fahrenheit(fahrenheit: f32) -> Temperature {
+        Temperature::from_celsius((fahrenheit - 32.0) * 5.0 / 9.0)
+    }
+
+    /// Create a new temperature from tenths of degrees Celsius, as sent by the sensor.
+    pub fn from_tenths(tenths: i16) -> Temperature {
+        Temperature::from_celsius(f32::from(tenths) / 10.0)
+    }
+
+    /// Get the temperature in degrees Celsius.
+    pub fn celsius(&self) -> f32 {
+        self.celsius
+    }
+
+    /// Get the temperature in degrees Fahrenheit.
+    pub fn fahrenheit(&self) -> f32 {
+        self.celsius * 9.0 / 5.0 + 32.0
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} °C", self.celsius)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// A relative humidity.
+pub struct Humidity {
+    /// The relative humidity in percent.
+    percent: f32,
+}
+
+impl Humidity {
+    /// Create a new relative humidity from percent.
+    pub fn from_percent(percent: f32) -> Humidity {
+        Humidity {
+            percent
+        }
+    }
+
+    /// Create a new relative humidity from tenths of percent, as sent by the sensor.
+    pub fn from_tenths(tenths: u16) -> Humidity {
+        Humidity::from_percent(f32::from(tenths) / 10.0)
+    }
+
+    /// Get the relative humidity in percent.
+    pub fn percent(&self) -> f32 {
+        self.percent
+    }
+}
+
+impl fmt::Display for Humidity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.1} %RH", self.percent)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// A battery level.
+pub struct BatteryLevel {
+    /// The battery level in percent.
+    percent: u8,
+}
+
+impl BatteryLevel {
+    /// Create a new battery level from percent.
+    pub fn from_percent(percent: u8) -> BatteryLevel {
+        BatteryLevel {
+            percent
+        }
+    }
+
+    /// Get the battery level in percent.
+    pub fn percent(&self) -> u8 {
+        self.percent
+    }
+}
+
+impl fmt::Display for BatteryLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} %", self.percent)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// A voltage.
+pub struct Voltage {
+    /// The voltage in volts.
+    volts: f32,
+}
+
+impl Voltage {
+    /// Create a new voltage from volts.
+    pub fn from_volts(volts: f32) -> Voltage {
+        Voltage {
+            volts
+        }
+    }
+
+    /// Create a new voltage from millivolts.
+    pub fn from_millivolts(millivolts: u16) -> Voltage {
+        Voltage::from_volts(f32::from(millivolts) / 1000.0)
+    }
+
+    /// Get the voltage in volts.
+    pub fn volts(&self) -> f32 {
+        self.volts
+    }
+
+    /// Get the voltage in millivolts.
+    pub fn millivolts(&self) -> u16 {
+        (self.volts * 1000.0).round() as u16
+    }
+}
+
+impl fmt::Display for Voltage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.3} V", self.volts)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// A reading of the mijiabt sensor, with explicit units.
+pub struct Reading {
+    /// The temperature, if the sensor sent it.
+    pub temperature: Option<Temperature>,
+    /// The relative humidity, if the sensor sent it.
+    pub humidity: Option<Humidity>,
+    /// The battery level, if the sensor sent it.
+    pub battery: Option<BatteryLevel>,
+    /// The battery voltage, if the sensor sent it.
+    pub voltage: Option<Voltage>,
+}
+
+impl Reading {
+    /// Get the dew point, below which water condenses.
+    ///
+    /// Returns the dew point, or None if it cannot be computed from this reading.
+    pub fn dew_point(&self) -> Option<Temperature> {
+        climate::dew_point(self.temperature?, self.humidity?)
+    }
+
+    /// Get the absolute humidity.
+    ///
+    /// Returns the absolute humidity in g/m³, or None if it cannot be computed from this reading.
+    pub fn absolute_humidity(&self) -> Option<f32> {
+        Some(climate::absolute_humidity(self.temperature?, self.humidity?))
+    }
+
+    /// Get the heat index, the temperature felt by the human body (NOAA).
+    ///
+    /// Returns the heat index, or None if it cannot be computed from this reading.
+    pub fn heat_index(&self) -> Option<Temperature> {
+        Some(climate::heat_index(self.temperature?, self.humidity?))
+    }
+
+    /// Get the humidex, the temperature felt by the human body (Environment Canada).
+    ///
+    /// Returns the humidex, or None if it cannot be computed from this reading.
+    pub fn humidex(&self) -> Option<f32> {
+        climate::humidex(self.temperature?, self.humidity?)
+    }
+
+    /// Get the vapour pressure deficit.
+    ///
+    /// Returns the vapour pressure deficit in kPa, or None if it cannot be computed from this reading.
+    pub fn vapour_pressure_deficit(&self) -> Option<f32> {
+        Some(climate::vapour_pressure_deficit(self.temperature?, self.humidity?))
+    }
+
+    /// Get all the climate metrics derived from this reading.
+    ///
+    /// Returns the climate metrics, or None if the reading lacks the temperature or the humidity.
+    pub fn climate_metrics(&self) -> Option<ClimateMetrics> {
+        Some(ClimateMetrics::compute(self.temperature?, self.humidity?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A quantity measured by the mijiabt sensor.
+pub enum Quantity {
+    /// The temperature, in °C.
+    Temperature,
+    /// The relative humidity, in %RH.
+    Humidity,
+    /// The battery level, in percent.
+    Battery,
+    /// The battery voltage, in volts.
+    Voltage,
+}
+
+impl Quantity {
+    /// Get the value of the quantity in a reading.
+    ///
+    /// # Arguments:
+    /// * `reading` - The reading.
+    ///
+    /// Returns the value, in the unit of the quantity, or None if the reading does not contain it.
+    pub fn value(&self, reading: &Reading) -> Option<f32> {
+        match self {
+            Quantity::Temperature => reading.temperature.map(|temperature| temperature.celsius()),
+            Quantity::Humidity => reading.humidity.map(|humidity| humidity.percent()),
+            Quantity::Battery => reading.battery.map(|battery| f32::from(battery.percent())),
+            Quantity::Voltage => reading.voltage.map(|voltage| voltage.volts())
+        }
+    }
+
+    /// Get the unit of the quantity.
+    ///
+    /// Returns the symbol of the unit, e.g. °C.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Quantity::Temperature => "°C",
+            Quantity::Humidity => "%RH",
+            Quantity::Battery => "%",
+            Quantity::Voltage => "V"
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! The stable, versioned JSON representation of a mijiabt sensor reading.
+//!
+//! Schema version 1:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "device_id": "A4:C1:38:00:00:01",
+//!   "name": "Living room",
+//!   "room": "Ground floor",
+//!   "tags": { "floor": "0" },
+//!   "timestamp": 1760870400123,
+//!   "quantities": {
+//!     "temperature": { "value": 21.5, "unit": "°C" },
+//!     "humidity": { "value": 45.2, "unit": "%RH" },
+//!     "battery": { "value": 87.0, "unit": "%" }
+//!   },
+//!   "climate": {
+//!     "dew_point": { "value": 9.1, "unit": "°C" },
+//!     "absolute_humidity": { "value": 8.5, "unit": "g/m³" },
+//!     "heat_index": { "value": 20.9, "unit": "°C" },
+//!     "humidex": { "value": 22.4, "unit": "°C" },
+//!     "vapour_pressure_deficit": { "value": 1.4, "unit": "kPa" }
+//!   },
+//!   "rssi": -72,
+//!   "adapter": "hci0"
+//! }
+//! ```
+//!
+//! * `device_id` is the MAC address of the sensor, in upper case.
+//! * `name` is the registered name of the sensor, or its MAC address if it is not registered.
+//! * `timestamp` is the reception time, in milliseconds since the Unix epoch.
+//! * `tags` are the key-value tags of the registered sensor, and are omitted when there are none.
+//! * `climate` holds the metrics derived from the calibrated temperature and humidity, and is omitted unless both are known.
+//! * `room`, `rssi`, `adapter`, each quantity and each climate metric are omitted when unknown.
+//!
+//! Fields may be added within a schema version; removing or changing a field bumps the version.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Serialize, Deserialize};
+
+use crate::mijiabt_data::{BatteryLevel, ClimateMetrics, Humidity, Quantity, Reading, ReadingSnapshot, Temperature, Voltage};
+
+/// The units of the climate metrics.
+const ABSOLUTE_HUMIDITY_UNIT: &str = "g/m³";
+const VAPOUR_PRESSURE_DEFICIT_UNIT: &str = "kPa";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A value of a quantity, along with its unit.
+pub struct Measurement {
+    /// The value, in the unit.
+    pub value: f32,
+    /// The symbol of the unit, e.g. °C.
+    pub unit: String,
+}
+
+impl Measurement {
+    /// Create a new instance of Measurement, in the unit of the quantity.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The measured quantity.
+    /// * `value` - The value, in the unit of the quantity.
+    ///
+    /// Returns a new instance of Measurement.
+    pub fn new(quantity: Quantity, value: f32) -> Measurement {
+        Measurement {
+            value,
+            unit: String::from(quantity.unit())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// The quantities of a reading event.
+pub struct Quantities {
+    /// The temperature, in °C.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<Measurement>,
+    /// The relative humidity, in %RH.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humidity: Option<Measurement>,
+    /// The battery level, in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery: Option<Measurement>,
+    /// The battery voltage, in volts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voltage: Option<Measurement>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+/// The climate metrics of a reading event, derived from the calibrated temperature and humidity.
+pub struct ClimateQuantities {
+    /// The dew point, in °C.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dew_point: Option<Measurement>,
+    /// The absolute humidity, in g/m³.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute_humidity: Option<Measurement>,
+    /// The heat index, in °C.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heat_index: Option<Measurement>,
+    /// The humidex, on the °C scale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humidex: Option<Measurement>,
+    /// The vapour pressure deficit, in kPa.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vapour_pressure_deficit: Option<Measurement>,
+}
+
+impl From<&ClimateMetrics> for ClimateQuantities {
+    fn from(climate: &ClimateMetrics) -> ClimateQuantities {
+        let measurement = |value: Option<f32>, unit: &str| value.map(|value| Measurement { value, unit: String::from(unit) });
+        let celsius = Quantity::Temperature.unit();
+
+        ClimateQuantities {
+            dew_point: measurement(climate.dew_point.map(|dew_point| dew_point.celsius()), celsius),
+            absolute_humidity: measurement(climate.absolute_humidity, ABSOLUTE_HUMIDITY_UNIT),
+            heat_index: measurement(climate.heat_index.map(|heat_index| heat_index.celsius()), celsius),
+            humidex: measurement(climate.humidex, celsius),
+            vapour_pressure_deficit: measurement(climate.vapour_pressure_deficit, VAPOUR_PRESSURE_DEFICIT_UNIT)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A reading of a mijiabt sensor, as exchanged with other systems.
+pub struct ReadingEvent {
+    /// The version of the schema of the event.
+    schema_version: u32,
+    /// The MAC address of the sensor.
+    device_id: String,
+    /// The display name of the sensor.
+    name: String,
+    /// The room of the sensor, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    room: Option<String>,
+    /// The tags of the sensor.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<String, String>,
+    /// The reception time, in milliseconds since the Unix epoch.
+    timestamp: u64,
+    /// The calibrated quantities.
+    quantities: Quantities,
+    /// The climate metrics, if the temperature and the humidity are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    climate: Option<ClimateQuantities>,
+    /// The received signal strength, in dBm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rssi: Option<i16>,
+    /// The name of the adapter the reading has been received on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    adapter: Option<String>,
+}
+
+impl ReadingEvent {
+    /// The version of the schema produced by this library.
+    pub const SCHEMA_VERSION: u32 = 1;
+
+    /// Serialize the event to JSON.
+    ///
+    /// Returns the JSON document, or an error if the event could not be serialized.
+    pub fn to_json(&self) -> Result<String, ReadingEventError> {
+        serde_json::to_string(self).map_err(ReadingEventError::Json)
+    }
+
+    /// Deserialize an event from JSON.
+    ///
+    /// # Arguments:
+    /// * `json` - The JSON document.
+    ///
+    /// Returns the event, or an error if the document is invalid or uses an unsupported schema version.
+    pub fn from_json(json: &str) -> Result<ReadingEvent, ReadingEventError> {
+        let event: ReadingEvent = serde_json::from_str(json).map_err(ReadingEventError::Json)?;
+
+        if event.schema_version != Self::SCHEMA_VERSION {
+            return Err(ReadingEventError::UnsupportedSchemaVersion(event.schema_version));
+        }
+
+        Ok(event)
+    }
+
+    /// Get the version of the schema of the event.
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// Get the MAC address of the sensor.
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// Get the display name of the sensor.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the room of the sensor.
+    ///
+    /// Returns the room, or None if it is unknown.
+    pub fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    /// Get the tags of the sensor.
+    pub fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
+    /// Get the reception time of the reading.
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp)
+    }
+
+    /// Get the calibrated quantities.
+    pub fn quantities(&self) -> &Quantities {
+        &self.quantities
+    }
+
+    /// Get the climate metrics.
+    ///
+    /// Returns the climate metrics, or None if the temperature or the humidity is unknown.
+    pub fn climate(&self) -> Option<&ClimateQuantities> {
+        self.climate.as_ref()
+    }
+
+    /// Get the received signal strength, in dBm.
+    pub fn rssi(&self) -> Option<i16> {
+        self.rssi
+    }
+
+    /// Get the name of the adapter the reading has been received on.
+    pub fn adapter(&self) -> Option<&str> {
+        self.adapter.as_deref()
+    }
+
+    /// Convert the quantities of the event back to a reading.
+    ///
+    /// Returns the reading.
+    pub fn reading(&self) -> Reading {
+        Reading {
+            temperature: self.quantities.temperature.as_ref().map(|measurement| Temperature::from_celsius(measurement.value)),
+            humidity: self.quantities.humidity.as_ref().map(|measurement| Humidity::from_percent(measurement.value)),
+            battery: self.quantities.battery.as_ref().map(|measurement| BatteryLevel::from_percent(measurement.value.round() as u8)),
+            voltage: self.quantities.voltage.as_ref().map(|measurement| Voltage::from_volts(measurement.value))
+        }
+    }
+}
+
+impl From<&ReadingSnapshot> for ReadingEvent {
+    fn from(snapshot: &ReadingSnapshot) -> ReadingEvent {
+        let reading = snapshot.reading();
+        let metadata = snapshot.metadata();
+        let measurement = |quantity: Quantity| quantity.value(reading).map(|value| Measurement::new(quantity, value));
+
+        ReadingEvent {
+            schema_version: Self::SCHEMA_VERSION,
+            device_id: String::from(snapshot.address()),
+            name: String::from(snapshot.name()),
+            room: snapshot.info().and_then(|info| info.room.clone()),
+            tags: snapshot.info().map(|info| info.tags.clone()).unwrap_or_default(),
+            timestamp: metadata.received_at.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis() as u64),
+            quantities: Quantities {
+                temperature: measurement(Quantity::Temperature),
+                humidity: measurement(Quantity::Humidity),
+                battery: measurement(Quantity::Battery),
+                voltage: measurement(Quantity::Voltage)
+            },
+            climate: snapshot.climate().map(ClimateQuantities::from),
+            rssi: metadata.rssi,
+            adapter: Some(metadata.adapter.clone()).filter(|adapter| !adapter.is_empty())
+        }
+    }
+}
+
+#[derive(Debug)]
+/// An error while exchanging a reading event.
+pub enum ReadingEventError {
+    /// The JSON document is invalid.
+    Json(serde_json::Error),
+    /// The schema version of the event is not supported.
+    UnsupportedSchemaVersion(u32),
+}
+
+impl fmt::Display for ReadingEventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadingEventError::Json(error) => write!(f, "Invalid reading event: {}", error),
+            ReadingEventError::UnsupportedSchemaVersion(version) => write!(f, "Unsupported reading event schema version: {}", version)
+        }
+    }
+}
+
+impl Error for ReadingEventError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadingEventError::Json(error) => Some(error),
+            ReadingEventError::UnsupportedSchemaVersion(_) => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mijiabt_data::ReceiveMetadata;
+    use crate::sensor::SensorInfo;
+
+    /// Build a reading event of a registered sensor, with every field set.
+    fn reading_event() -> ReadingEvent {
+        let mut info = SensorInfo::new("Living room");
+        info.room = Some(String::from("Ground floor"));
+        info.tags.insert(String::from("floor"), String::from("0"));
+
+        let reading = Reading {
+            temperature: Some(Temperature::from_tenths(215)),
+            humidity: Some(Humidity::from_tenths(452)),
+            battery: Some(BatteryLevel::from_percent(87)),
+            voltage: None
+        };
+        let metadata = ReceiveMetadata::now(Some(3), Some(-72), "hci0");
+
+        ReadingEvent::from(&ReadingSnapshot::new("A4:C1:38:00:00:01", Some(info), reading, reading, metadata))
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let event = reading_event();
+        assert!(event.climate().is_some());
+
+        let json = event.to_json().unwrap();
+        let parsed = ReadingEvent::from_json(&json).unwrap();
+
+        assert_eq!(parsed, event);
+        assert_eq!(parsed.tags().get("floor").map(String::as_str), Some("0"));
+        assert_eq!(parsed.reading().temperature, Some(Temperature::from_tenths(215)));
+    }
+
+    #[test]
+    fn rejects_other_schema_versions() {
+        let json = reading_event().to_json().unwrap().replace("\"schema_version\":1", "\"schema_version\":2");
+
+        assert!(matches!(ReadingEvent::from_json(&json), Err(ReadingEventError::UnsupportedSchemaVersion(2))));
+    }
+
+    #[test]
+    fn accepts_missing_optional_quantities() {
+        let json = r#"{
+            "schema_version": 1,
+            "device_id": "A4:C1:38:00:00:01",
+            "name": "A4:C1:38:00:00:01",
+            "timestamp": 1760870400123,
+            "quantities": { "temperature": { "value": 21.5, "unit": "°C" } }
+        }"#;
+
+        let event = ReadingEvent::from_json(json).unwrap();
+
+        assert_eq!(event.quantities().temperature, Some(Measurement::new(Quantity::Temperature, 21.5)));
+        assert_eq!(event.quantities().humidity, None);
+        assert_eq!(event.reading().battery, None);
+        assert_eq!(event.room(), None);
+        assert!(event.tags().is_empty());
+        assert_eq!(event.climate(), None);
+        assert_eq!(event.rssi(), None);
+    }
+}
@@ -0,0 +1,128 @@
+use std::time::{Instant, SystemTime};
+
+use serde::{Serialize, Deserialize};
+
+use crate::mijiabt_data::{ClimateMetrics, Reading};
+use crate::sensor::SensorInfo;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The metadata about the reception of a mijia bt advertisement.
+pub struct ReceiveMetadata {
+    /// The wall clock time the advertisement has been received at.
+    pub received_at: SystemTime,
+    /// The monotonic time the advertisement has been received at.
+    /// It is not serialized, and is set to the deserialization time when deserialized.
+    #[serde(skip, default = "Instant::now")]
+    pub received_instant: Instant,
+    /// The MiBeacon frame counter of the advertisement, if any.
+    pub frame_counter: Option<u8>,
+    /// The received signal strength of the advertisement, in dBm.
+    pub rssi: Option<i16>,
+    /// The name of the adapter the advertisement has been received on, e.g. hci0.
+    pub adapter: String,
+}
+
+impl ReceiveMetadata {
+    /// Create a new instance of ReceiveMetadata for an advertisement received now.
+    ///
+    /// # Arguments:
+    /// * `frame_counter` - The MiBeacon frame counter of the advertisement, if any.
+    /// * `rssi` - The received signal strength of the advertisement, if any.
+    /// * `adapter` - The name of the adapter the advertisement has been received on.
+    ///
+    /// Returns a new instance of ReceiveMetadata.
+    pub fn now(frame_counter: Option<u8>, rssi: Option<i16>, adapter: &str) -> ReceiveMetadata {
+        ReceiveMetadata {
+            received_at: SystemTime::now(),
+            received_instant: Instant::now(),
+            frame_counter,
+            rssi,
+            adapter: String::from(adapter)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// An immutable snapshot of a mijiabt sensor reading, along with its reception metadata.
+pub struct ReadingSnapshot {
+    /// The MAC address of the sensor.
+    address: String,
+    /// The registered information about the sensor, if any.
+    info: Option<SensorInfo>,
+    /// The calibrated reading.
+    reading: Reading,
+    /// The reading before calibration.
+    raw_reading: Reading,
+    /// The climate metrics derived from the calibrated reading, if it contains the temperature and the humidity.
+    climate: Option<ClimateMetrics>,
+    /// The reception metadata.
+    #[serde(flatten)]
+    metadata: ReceiveMetadata,
+}
+
+impl ReadingSnapshot {
+    /// Create a new instance of ReadingSnapshot.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    /// * `info` - The registered information about the sensor, if any.
+    /// * `reading` - The calibrated reading.
+    /// * `raw_reading` - The reading before calibration.
+    /// * `metadata` - The reception metadata.
+    ///
+    /// Returns a new instance of ReadingSnapshot.
+    pub fn new(address: &str, info: Option<SensorInfo>, reading: Reading, raw_reading: Reading, metadata: ReceiveMetadata) -> ReadingSnapshot {
+        ReadingSnapshot {
+            address: String::from(address),
+            info,
+            climate: reading.climate_metrics(),
+            reading,
+            raw_reading,
+            metadata
+        }
+    }
+
+    /// Get the MAC address of the sensor.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Get the registered information about the sensor.
+    ///
+    /// Returns the information, or None if the sensor is not registered.
+    pub fn info(&self) -> Option<&SensorInfo> {
+        self.info.as_ref()
+    }
+
+    /// Get the display name of the sensor.
+    ///
+    /// Returns the registered name of the sensor, or its MAC address if it is not registered.
+    pub fn name(&self) -> &str {
+        match &self.info {
+            None => &self.address,
+            Some(info) => &info.name
+        }
+    }
+
+    /// Get the calibrated reading.
+    pub fn reading(&self) -> &Reading {
+        &self.reading
+    }
+
+    /// Get the reading before calibration.
+    pub fn raw_reading(&self) -> &Reading {
+        &self.raw_reading
+    }
+
+    /// Get the climate metrics derived from the calibrated reading.
+    ///
+    /// Returns the climate metrics, or None if the reading lacks the temperature or the humidity.
+    pub fn climate(&self) -> Option<&ClimateMetrics> {
+        self.climate.as_ref()
+    }
+
+    /// Get the reception metadata.
+    pub fn metadata(&self) -> &ReceiveMetadata {
+        &self.metadata
+    }
+}
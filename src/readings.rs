@@ -0,0 +1,41 @@
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::mijiabt_data::ReadingSnapshot;
+
+/// A blocking iterator over the mijia bt readings.
+/// It yields a snapshot of each decoded reading in order, and ends once the listening stops.
+pub struct Readings {
+    /// The receiving end of the readings channel.
+    receiver: Receiver<ReadingSnapshot>
+}
+
+impl Readings {
+    /// Return a new readings iterator.
+    ///
+    /// # Arguments:
+    /// * `receiver` - The receiving end of the readings channel.
+    pub(crate) fn new(receiver: Receiver<ReadingSnapshot>) -> Readings {
+        Readings {
+            receiver
+        }
+    }
+
+    /// Wait for the next reading.
+    ///
+    /// # Arguments:
+    /// * `timeout` - The maximum time to wait for the reading.
+    ///
+    /// Returns the next reading, or an error if the timeout elapsed or the listening stopped.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<ReadingSnapshot, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+}
+
+impl Iterator for Readings {
+    type Item = ReadingSnapshot;
+
+    fn next(&mut self) -> Option<ReadingSnapshot> {
+        self.receiver.recv().ok()
+    }
+}
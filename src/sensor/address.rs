@@ -0,0 +1,28 @@
+/// Normalize a MAC address so that 4c-65-a8-da-8f-12 matches 4C:65:A8:DA:8F:12.
+/// Anything which does not look like a MAC address, such as an alias, is kept as is.
+///
+/// # Arguments:
+/// * `device` - The MAC address or the bluez alias of a device.
+///
+/// Returns the normalized MAC address, or the unchanged input.
+pub(crate) fn normalize_address(device: &str) -> String {
+    parse_address(device).unwrap_or_else(|| String::from(device))
+}
+
+/// Parse a MAC address, separated by colons or dashes, e.g. a4-c1-38-00-00-01.
+///
+/// # Arguments:
+/// * `address` - The MAC address.
+///
+/// Returns the MAC address in upper case separated by colons, e.g. A4:C1:38:00:00:01, or None if it is not a MAC address.
+pub fn parse_address(address: &str) -> Option<String> {
+    let is_address = address.len() == 17 && address.chars().enumerate().all(|(i, c)| {
+        if i % 3 == 2 { c == ':' || c == '-' } else { c.is_ascii_hexdigit() }
+    });
+
+    if is_address {
+        Some(address.to_uppercase().replace('-', ":"))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A linear correction of a measured quantity: calibrated = raw * scale + offset.
+/// When deserialized, a missing offset defaults to 0 and a missing scale to 1.
+pub struct LinearCalibration {
+    /// The offset added to the scaled value, in the unit of the quantity.
+    pub offset: f32,
+    /// The factor the raw value is multiplied by.
+    pub scale: f32,
+}
+
+impl LinearCalibration {
+    /// Create a new instance of LinearCalibration.
+    ///
+    /// # Arguments:
+    /// * `offset` - The offset added to the scaled value.
+    /// * `scale` - The factor the raw value is multiplied by.
+    ///
+    /// Returns a new LinearCalibration.
+    pub fn new(offset: f32, scale: f32) -> LinearCalibration {
+        LinearCalibration {
+            offset,
+            scale
+        }
+    }
+
+    /// Compute a calibration from two reference points.
+    ///
+    /// # Arguments:
+    /// * `raw_low` - The value measured by the sensor at the low reference point.
+    /// * `reference_low` - The actual value at the low reference point.
+    /// * `raw_high` - The value measured by the sensor at the high reference point.
+    /// * `reference_high` - The actual value at the high reference point.
+    ///
+    /// Returns the calibration mapping both measured values to the actual ones,
+    /// or None if both measured values are equal.
+    pub fn from_two_points(raw_low: f32, reference_low: f32, raw_high: f32, reference_high: f32) -> Option<LinearCalibration> {
+        if raw_high == raw_low {
+            return None;
+        }
+
+        let scale = (reference_high - reference_low) / (raw_high - raw_low);
+
+        Some(LinearCalibration::new(reference_low - raw_low * scale, scale))
+    }
+
+    /// Apply the calibration to a raw value.
+    ///
+    /// # Arguments:
+    /// * `raw` - The raw value.
+    ///
+    /// Returns the calibrated value.
+    pub fn apply(&self, raw: f32) -> f32 {
+        raw * self.scale + self.offset
+    }
+}
+
+impl Default for LinearCalibration {
+    fn default() -> LinearCalibration {
+        LinearCalibration::new(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// The calibration of a mijia bt sensor.
+pub struct Calibration {
+    /// The temperature correction, in °C.
+    pub temperature: LinearCalibration,
+    /// The humidity correction, in %RH.
+    pub humidity: LinearCalibration,
+}
+
+impl Calibration {
+    /// Create a new instance of Calibration.
+    ///
+    /// Returns a new Calibration leaving the values unchanged.
+    pub fn new() -> Calibration {
+        Calibration::default()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::sensor::address::normalize_address;
+
+#[derive(Debug, Clone, Default)]
+/// A filter selecting the sensors to listen by MAC address or bluez alias.
+pub struct DeviceFilter {
+    /// The allowed MAC addresses and aliases. If empty, every device which is not denied is allowed.
+    allowlist: Vec<String>,
+    /// The denied MAC addresses and aliases.
+    denylist: Vec<String>,
+}
+
+impl DeviceFilter {
+    /// Create a new instance of DeviceFilter.
+    ///
+    /// Returns a new DeviceFilter allowing every device.
+    pub fn new() -> DeviceFilter {
+        DeviceFilter {
+            allowlist: Vec::new(),
+            denylist: Vec::new()
+        }
+    }
+
+    /// Add a device to the allowlist.
+    /// Once the allowlist is not empty, only the devices it contains are allowed.
+    ///
+    /// # Arguments:
+    /// * `device` - The MAC address or the bluez alias of the device.
+    pub fn allow(&mut self, device: &str) {
+        self.allowlist.push(normalize_address(device));
+    }
+
+    /// Add a device to the denylist.
+    /// The denylist takes precedence over the allowlist.
+    ///
+    /// # Arguments:
+    /// * `device` - The MAC address or the bluez alias of the device.
+    pub fn deny(&mut self, device: &str) {
+        self.denylist.push(normalize_address(device));
+    }
+
+    /// Check whether a device is allowed.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the device.
+    /// * `alias` - The bluez alias of the device.
+    ///
+    /// Returns true whether the device is allowed, false otherwise.
+    pub fn is_allowed(&self, address: &str, alias: &str) -> bool {
+        let address = normalize_address(address);
+        let matches = |entry: &String| *entry == address || entry == alias;
+
+        if self.denylist.iter().any(matches) {
+            return false;
+        }
+
+        self.allowlist.is_empty() || self.allowlist.iter().any(matches)
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Serialize, Deserialize};
+
+/// The largest forward gap between two frame counters which is counted as missed frames.
+/// A larger gap cannot be told apart from a frame received out of order, or from a counter reset.
+const MAX_FORWARD_GAP: u8 = 127;
+/// The number of frames a frame counter may be behind the last one to be considered an older frame
+/// received out of order or retransmitted, rather than a counter reset.
+const REORDER_WINDOW: u8 = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+/// The link quality of a sensor, measured from the MiBeacon frame counters.
+pub struct LinkQuality {
+    /// The number of distinct frames received.
+    pub received: u64,
+    /// The number of duplicate frames dropped, including the older frames received out of order.
+    pub duplicates: u64,
+    /// The number of frames missed, according to the gaps in the frame counters.
+    pub lost: u64,
+}
+
+impl LinkQuality {
+    /// Get the packet loss ratio.
+    ///
+    /// Returns the ratio of missed frames among the frames sent by the sensor, between 0 and 1.
+    pub fn loss_ratio(&self) -> f32 {
+        let sent = self.received + self.lost;
+        if sent == 0 {
+            0.0
+        } else {
+            self.lost as f32 / sent as f32
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A tracker of the MiBeacon frame counters of a sensor.
+pub(crate) struct FrameCounterTracker {
+    /// The frame counter of the last distinct frame, if any.
+    last_frame_counter: Option<u8>,
+    /// The link quality measured so far.
+    link_quality: LinkQuality,
+}
+
+impl FrameCounterTracker {
+    /// Track a received frame.
+    ///
+    /// # Arguments:
+    /// * `frame_counter` - The frame counter of the frame.
+    ///
+    /// Returns true whether the frame is a new one, false if it is a duplicate of the last one,
+    /// or an older frame received out of order or retransmitted.
+    pub(crate) fn track(&mut self, frame_counter: u8) -> bool {
+        if let Some(last_frame_counter) = self.last_frame_counter {
+            let gap = frame_counter.wrapping_sub(last_frame_counter);
+            let backward_gap = last_frame_counter.wrapping_sub(frame_counter);
+
+            // A counter equal to or slightly behind the last one is a duplicate, or an older frame received late.
+            if backward_gap <= REORDER_WINDOW {
+                self.link_quality.duplicates += 1;
+                return false;
+            }
+
+            // The counter wraps around, so only the short forward gaps are counted as missed frames.
+            // A larger jump is taken as a counter reset, e.g. when the sensor reboots, and the tracking starts over.
+            if gap <= MAX_FORWARD_GAP {
+                self.link_quality.lost += u64::from(gap - 1);
+            }
+        }
+
+        self.link_quality.received += 1;
+        self.last_frame_counter = Some(frame_counter);
+
+        true
+    }
+
+    /// Forget the last frame counter, keeping the link quality measured so far.
+    /// The next frame is taken as a new one whatever its counter, e.g. once the sensor has been lost and may have rebooted.
+    pub(crate) fn reset(&mut self) {
+        self.last_frame_counter = None;
+    }
+
+    /// Get the link quality measured so far.
+    pub(crate) fn link_quality(&self) -> LinkQuality {
+        self.link_quality
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Track a sequence of frame counters.
+    ///
+    /// Returns the tracker, and whether each frame was a new one.
+    fn track(frame_counters: &[u8]) -> (FrameCounterTracker, Vec<bool>) {
+        let mut tracker = FrameCounterTracker::default();
+        let new_frames = frame_counters.iter().map(|frame_counter| tracker.track(*frame_counter)).collect();
+        (tracker, new_frames)
+    }
+
+    #[test]
+    fn drops_duplicates() {
+        let (tracker, new_frames) = track(&[10, 10, 11, 11]);
+
+        assert_eq!(new_frames, vec![true, false, true, false]);
+        assert_eq!(tracker.link_quality(), LinkQuality { received: 2, duplicates: 2, lost: 0 });
+    }
+
+    #[test]
+    fn counts_the_gaps_as_lost() {
+        let (tracker, new_frames) = track(&[10, 11, 14]);
+
+        assert_eq!(new_frames, vec![true, true, true]);
+        assert_eq!(tracker.link_quality(), LinkQuality { received: 3, duplicates: 0, lost: 2 });
+        assert_eq!(tracker.link_quality().loss_ratio(), 0.4);
+    }
+
+    #[test]
+    fn follows_the_counter_around() {
+        let (tracker, new_frames) = track(&[254, 255, 0, 2]);
+
+        assert_eq!(new_frames, vec![true, true, true, true]);
+        assert_eq!(tracker.link_quality(), LinkQuality { received: 4, duplicates: 0, lost: 1 });
+    }
+
+    #[test]
+    fn drops_older_frames_received_out_of_order() {
+        let (tracker, new_frames) = track(&[20, 22, 21, 6, 23]);
+
+        assert_eq!(new_frames, vec![true, true, false, false, true]);
+        assert_eq!(tracker.link_quality(), LinkQuality { received: 3, duplicates: 2, lost: 1 });
+    }
+
+    #[test]
+    fn drops_older_frames_around_the_wrap() {
+        let (_, new_frames) = track(&[254, 1, 255]);
+
+        assert_eq!(new_frames, vec![true, true, false]);
+    }
+
+    #[test]
+    fn takes_a_large_jump_as_a_counter_reset() {
+        let (tracker, new_frames) = track(&[10, 200, 201]);
+
+        assert_eq!(new_frames, vec![true, true, true]);
+        assert_eq!(tracker.link_quality(), LinkQuality { received: 3, duplicates: 0, lost: 0 });
+    }
+
+    #[test]
+    fn accepts_any_counter_once_reset() {
+        let (mut tracker, _) = track(&[10, 11]);
+
+        tracker.reset();
+
+        assert!(tracker.track(5));
+        assert!(tracker.track(6));
+        assert_eq!(tracker.link_quality(), LinkQuality { received: 4, duplicates: 0, lost: 0 });
+    }
+}
@@ -0,0 +1,22 @@
+mod address;
+mod calibration;
+mod device_filter;
+mod link_quality;
+mod reading_filter;
+mod reporting_policy;
+mod sensor_history;
+mod sensor_registry;
+mod sensor_statistics;
+
+pub(crate) use address::normalize_address;
+pub use address::parse_address;
+pub use calibration::{Calibration, LinearCalibration};
+pub use device_filter::DeviceFilter;
+pub use link_quality::LinkQuality;
+pub(crate) use link_quality::FrameCounterTracker;
+pub use reading_filter::{ExponentialMovingAverage, MedianFilter, RangeFilter, ReadingFilter};
+pub use reporting_policy::{QuantityPolicy, ReportingPolicy};
+pub(crate) use reporting_policy::ReportState;
+pub use sensor_history::SensorHistory;
+pub use sensor_registry::{SensorInfo, SensorRegistry};
+pub use sensor_statistics::SensorStatistics;
@@ -0,0 +1,223 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::mijiabt_data::Quantity;
+
+/// A filter applied to the values of a sensor before they are reported.
+/// Each sensor gets its own filter instances, so that filters can keep a state.
+pub trait ReadingFilter: Send {
+    /// Filter a new value of a quantity.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The quantity.
+    /// * `value` - The new calibrated value, in the unit of the quantity.
+    ///
+    /// Returns the filtered value, or None to reject the value.
+    fn filter(&mut self, quantity: Quantity, value: f32) -> Option<f32>;
+}
+
+#[derive(Debug, Clone)]
+/// A filter rejecting the values out of a plausible range, such as the spikes produced by corrupted advertisements.
+pub struct RangeFilter {
+    /// The plausible (min, max) ranges, by quantity. The quantities without a range are not checked.
+    ranges: HashMap<Quantity, (f32, f32)>,
+}
+
+impl RangeFilter {
+    /// Create a new instance of RangeFilter.
+    ///
+    /// Returns a new RangeFilter with ranges plausible for the mijia bt sensor.
+    pub fn new() -> RangeFilter {
+        let mut range_filter = RangeFilter {
+            ranges: HashMap::new()
+        };
+
+        range_filter.set_range(Quantity::Temperature, -40.0, 85.0);
+        range_filter.set_range(Quantity::Humidity, 0.0, 100.0);
+        range_filter.set_range(Quantity::Battery, 0.0, 100.0);
+
+        range_filter
+    }
+
+    /// Set the plausible range of a quantity.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The quantity.
+    /// * `min` - The minimum plausible value, inclusive.
+    /// * `max` - The maximum plausible value, inclusive.
+    pub fn set_range(&mut self, quantity: Quantity, min: f32, max: f32) {
+        self.ranges.insert(quantity, (min, max));
+    }
+}
+
+impl Default for RangeFilter {
+    fn default() -> RangeFilter {
+        RangeFilter::new()
+    }
+}
+
+impl ReadingFilter for RangeFilter {
+    fn filter(&mut self, quantity: Quantity, value: f32) -> Option<f32> {
+        match self.ranges.get(&quantity) {
+            Some((min, max)) if value < *min || value > *max => None,
+            _ => Some(value)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A filter replacing each value by the median of the last values, which removes isolated spikes.
+pub struct MedianFilter {
+    /// The number of values the median is computed over.
+    window: usize,
+    /// The last values, by quantity.
+    values: HashMap<Quantity, VecDeque<f32>>,
+}
+
+impl MedianFilter {
+    /// Create a new instance of MedianFilter.
+    ///
+    /// # Arguments:
+    /// * `window` - The number of values the median is computed over.
+    ///
+    /// Returns a new MedianFilter.
+    pub fn new(window: usize) -> MedianFilter {
+        MedianFilter {
+            window: window.max(1),
+            values: HashMap::new()
+        }
+    }
+}
+
+impl ReadingFilter for MedianFilter {
+    fn filter(&mut self, quantity: Quantity, value: f32) -> Option<f32> {
+        let values = self.values.entry(quantity).or_default();
+        if values.len() == self.window {
+            values.pop_front();
+        }
+        values.push_back(value);
+
+        let mut sorted_values: Vec<f32> = values.iter().copied().collect();
+        sorted_values.sort_by(f32::total_cmp);
+
+        // Both indexes are the middle one for an odd number of values, and the two middle ones otherwise.
+        let len = sorted_values.len();
+        Some((sorted_values[(len - 1) / 2] + sorted_values[len / 2]) / 2.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A filter smoothing the values with an exponential moving average.
+pub struct ExponentialMovingAverage {
+    /// The weight of a new value, between 0 and 1. The lower, the smoother.
+    alpha: f32,
+    /// The current averages, by quantity.
+    averages: HashMap<Quantity, f32>,
+}
+
+impl ExponentialMovingAverage {
+    /// Create a new instance of ExponentialMovingAverage.
+    ///
+    /// # Arguments:
+    /// * `alpha` - The weight of a new value, between 0 and 1. The lower, the smoother.
+    ///
+    /// Returns a new ExponentialMovingAverage, with the weight clamped between 0 and 1, or None if the weight is NaN.
+    pub fn new(alpha: f32) -> Option<ExponentialMovingAverage> {
+        if alpha.is_nan() {
+            return None;
+        }
+
+        Some(ExponentialMovingAverage {
+            alpha: alpha.clamp(0.0, 1.0),
+            averages: HashMap::new()
+        })
+    }
+}
+
+impl ReadingFilter for ExponentialMovingAverage {
+    fn filter(&mut self, quantity: Quantity, value: f32) -> Option<f32> {
+        let average = match self.averages.get(&quantity) {
+            None => value,
+            Some(average) => self.alpha * value + (1.0 - self.alpha) * average
+        };
+
+        self.averages.insert(quantity, average);
+
+        Some(average)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_filter_rejects_implausible_values() {
+        let mut range_filter = RangeFilter::new();
+
+        assert_eq!(range_filter.filter(Quantity::Temperature, 21.5), Some(21.5));
+        assert_eq!(range_filter.filter(Quantity::Temperature, -40.0), Some(-40.0));
+        assert_eq!(range_filter.filter(Quantity::Temperature, 85.0), Some(85.0));
+        assert_eq!(range_filter.filter(Quantity::Temperature, 85.1), None);
+        assert_eq!(range_filter.filter(Quantity::Humidity, -0.1), None);
+        assert_eq!(range_filter.filter(Quantity::Battery, 101.0), None);
+    }
+
+    #[test]
+    fn range_filter_does_not_check_the_quantities_without_range() {
+        let mut range_filter = RangeFilter::new();
+        assert_eq!(range_filter.filter(Quantity::Voltage, 12.0), Some(12.0));
+
+        range_filter.set_range(Quantity::Voltage, 2.0, 3.3);
+        assert_eq!(range_filter.filter(Quantity::Voltage, 12.0), None);
+    }
+
+    #[test]
+    fn median_filter_removes_an_isolated_spike() {
+        let mut median_filter = MedianFilter::new(3);
+
+        assert_eq!(median_filter.filter(Quantity::Temperature, 21.0), Some(21.0));
+        assert_eq!(median_filter.filter(Quantity::Temperature, 22.0), Some(21.5));
+        assert_eq!(median_filter.filter(Quantity::Temperature, 80.0), Some(22.0));
+        assert_eq!(median_filter.filter(Quantity::Temperature, 23.0), Some(23.0));
+    }
+
+    #[test]
+    fn median_filter_keeps_the_quantities_apart() {
+        let mut median_filter = MedianFilter::new(3);
+
+        median_filter.filter(Quantity::Temperature, 21.0);
+        assert_eq!(median_filter.filter(Quantity::Humidity, 45.0), Some(45.0));
+    }
+
+    #[test]
+    fn median_filter_does_not_panic_on_nan() {
+        let mut median_filter = MedianFilter::new(3);
+
+        median_filter.filter(Quantity::Temperature, 21.0);
+        median_filter.filter(Quantity::Temperature, f32::NAN);
+        assert_eq!(median_filter.filter(Quantity::Temperature, 22.0), Some(22.0));
+    }
+
+    #[test]
+    fn exponential_moving_average_smooths_the_values() {
+        let mut average = ExponentialMovingAverage::new(0.5).unwrap();
+
+        assert_eq!(average.filter(Quantity::Humidity, 40.0), Some(40.0));
+        assert_eq!(average.filter(Quantity::Humidity, 50.0), Some(45.0));
+        assert_eq!(average.filter(Quantity::Humidity, 50.0), Some(47.5));
+        assert_eq!(average.filter(Quantity::Temperature, 20.0), Some(20.0));
+    }
+
+    #[test]
+    fn exponential_moving_average_clamps_its_weight() {
+        let mut average = ExponentialMovingAverage::new(2.0).unwrap();
+
+        average.filter(Quantity::Humidity, 40.0);
+        assert_eq!(average.filter(Quantity::Humidity, 50.0), Some(50.0));
+    }
+
+    #[test]
+    fn exponential_moving_average_rejects_a_nan_weight() {
+        assert!(ExponentialMovingAverage::new(f32::NAN).is_none());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::mijiabt_data::{Quantity, Reading};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+/// The reporting policy of a quantity.
+pub struct QuantityPolicy {
+    /// The minimum change from the last reported value to report, in the unit of the quantity.
+    /// If 0, any change is reported.
+    pub min_delta: f32,
+    /// The additional change required when the value moves back in the opposite direction
+    /// of the last reported change, to avoid reporting a value flickering around a threshold.
+    pub hysteresis: f32,
+    /// The minimum time between two reports of a change, if any.
+    pub min_interval: Option<Duration>,
+    /// The maximum time between two reports, if any.
+    /// Once elapsed, the value is reported with the next advertisement, even if unchanged.
+    pub max_interval: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The reporting policies of the quantities measured by a sensor.
+/// The quantities without an explicit policy are reported on any change.
+pub struct ReportingPolicy {
+    /// The policies, by quantity.
+    policies: HashMap<Quantity, QuantityPolicy>,
+}
+
+impl ReportingPolicy {
+    /// Create a new instance of ReportingPolicy.
+    ///
+    /// Returns a new ReportingPolicy reporting any change.
+    pub fn new() -> ReportingPolicy {
+        ReportingPolicy {
+            policies: HashMap::new()
+        }
+    }
+
+    /// Set the reporting policy of a quantity.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The quantity.
+    /// * `policy` - The reporting policy of the quantity.
+    pub fn set(&mut self, quantity: Quantity, policy: QuantityPolicy) {
+        self.policies.insert(quantity, policy);
+    }
+
+    /// Get the reporting policy of a quantity.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The quantity.
+    ///
+    /// Returns the reporting policy of the quantity.
+    pub fn get(&self, quantity: Quantity) -> QuantityPolicy {
+        self.policies.get(&quantity).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The last report of a quantity.
+struct QuantityReport {
+    /// The reported value.
+    value: f32,
+    /// The sign of the last reported change, or 0 if none.
+    direction: f32,
+    /// The time of the report.
+    reported_at: Instant,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The reporting state of a sensor, deciding whether its readings are reported.
+pub(crate) struct ReportState {
+    /// The last reports, by quantity.
+    reports: HashMap<Quantity, QuantityReport>,
+}
+
+impl ReportState {
+    /// The quantities a report decision is based on.
+    const QUANTITIES: [Quantity; 4] = [Quantity::Temperature, Quantity::Humidity, Quantity::Battery, Quantity::Voltage];
+
+    /// Check whether a reading has to be reported.
+    ///
+    /// # Arguments:
+    /// * `policy` - The reporting policy.
+    /// * `reading` - The reading.
+    /// * `now` - The time the reading has been received at.
+    ///
+    /// Returns true whether any quantity of the reading has to be reported, false otherwise.
+    pub(crate) fn should_report(&self, policy: &ReportingPolicy, reading: &Reading, now: Instant) -> bool {
+        Self::QUANTITIES.iter().any(|quantity| match quantity.value(reading) {
+            None => false,
+            Some(value) => match self.reports.get(quantity) {
+                None => true,
+                Some(report) => Self::should_report_quantity(&policy.get(*quantity), report, value, now)
+            }
+        })
+    }
+
+    /// Record that a reading has been reported.
+    ///
+    /// # Arguments:
+    /// * `reading` - The reading.
+    /// * `now` - The time the reading has been reported at.
+    pub(crate) fn reported(&mut self, reading: &Reading, now: Instant) {
+        for quantity in Self::QUANTITIES.iter() {
+            if let Some(value) = quantity.value(reading) {
+                let direction = match self.reports.get(quantity) {
+                    Some(report) if value == report.value => report.direction,
+                    Some(report) => (value - report.value).signum(),
+                    None => 0.0
+                };
+
+                self.reports.insert(*quantity, QuantityReport {
+                    value,
+                    direction,
+                    reported_at: now
+                });
+            }
+        }
+    }
+
+    /// Check whether a new value of a quantity has to be reported.
+    fn should_report_quantity(policy: &QuantityPolicy, report: &QuantityReport, value: f32, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(report.reported_at);
+
+        if let Some(max_interval) = policy.max_interval {
+            if elapsed >= max_interval {
+                return true;
+            }
+        }
+
+        let delta = value - report.value;
+        if delta == 0.0 {
+            return false;
+        }
+
+        let mut threshold = policy.min_delta;
+        if report.direction != 0.0 && delta.signum() != report.direction {
+            threshold += policy.hysteresis;
+        }
+
+        if delta.abs() < threshold {
+            return false;
+        }
+
+        match policy.min_interval {
+            Some(min_interval) => elapsed >= min_interval,
+            None => true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mijiabt_data::Temperature;
+
+    /// Build a reading with a temperature only.
+    fn temperature(celsius: f32) -> Reading {
+        Reading {
+            temperature: Some(Temperature::from_celsius(celsius)),
+            humidity: None,
+            battery: None,
+            voltage: None
+        }
+    }
+
+    /// Build a reporting policy for the temperature.
+    fn policy(quantity_policy: QuantityPolicy) -> ReportingPolicy {
+        let mut policy = ReportingPolicy::new();
+        policy.set(Quantity::Temperature, quantity_policy);
+        policy
+    }
+
+    /// Get the time some seconds after a start time.
+    fn at(start: Instant, seconds: u64) -> Instant {
+        start + Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn reports_the_first_reading_and_any_change_by_default() {
+        let policy = ReportingPolicy::new();
+        let start = Instant::now();
+        let mut state = ReportState::default();
+
+        assert!(state.should_report(&policy, &temperature(20.0), start));
+        state.reported(&temperature(20.0), start);
+
+        assert!(!state.should_report(&policy, &temperature(20.0), at(start, 1)));
+        assert!(state.should_report(&policy, &temperature(20.125), at(start, 1)));
+    }
+
+    #[test]
+    fn suppresses_a_flicker_below_min_delta() {
+        let policy = policy(QuantityPolicy { min_delta: 0.5, ..QuantityPolicy::default() });
+        let start = Instant::now();
+        let mut state = ReportState::default();
+        state.reported(&temperature(20.0), start);
+
+        assert!(!state.should_report(&policy, &temperature(20.25), at(start, 1)));
+        assert!(!state.should_report(&policy, &temperature(19.75), at(start, 2)));
+        assert!(state.should_report(&policy, &temperature(20.5), at(start, 3)));
+    }
+
+    #[test]
+    fn requires_the_hysteresis_on_a_reversal() {
+        let policy = policy(QuantityPolicy { min_delta: 0.5, hysteresis: 0.25, ..QuantityPolicy::default() });
+        let start = Instant::now();
+        let mut state = ReportState::default();
+        state.reported(&temperature(20.0), start);
+        state.reported(&temperature(20.5), at(start, 1));
+
+        assert!(!state.should_report(&policy, &temperature(20.0), at(start, 2)));
+        assert!(state.should_report(&policy, &temperature(19.75), at(start, 2)));
+        assert!(state.should_report(&policy, &temperature(21.0), at(start, 2)));
+    }
+
+    #[test]
+    fn sends_a_heartbeat_after_max_interval() {
+        let policy = policy(QuantityPolicy { min_delta: 0.5, max_interval: Some(Duration::from_secs(900)), ..QuantityPolicy::default() });
+        let start = Instant::now();
+        let mut state = ReportState::default();
+        state.reported(&temperature(20.0), start);
+
+        assert!(!state.should_report(&policy, &temperature(20.0), at(start, 899)));
+        assert!(state.should_report(&policy, &temperature(20.0), at(start, 900)));
+
+        state.reported(&temperature(20.0), at(start, 900));
+        assert!(!state.should_report(&policy, &temperature(20.0), at(start, 901)));
+    }
+
+    #[test]
+    fn defers_a_large_change_until_min_interval() {
+        let policy = policy(QuantityPolicy { min_delta: 0.5, min_interval: Some(Duration::from_secs(60)), ..QuantityPolicy::default() });
+        let start = Instant::now();
+        let mut state = ReportState::default();
+        state.reported(&temperature(20.0), start);
+
+        assert!(!state.should_report(&policy, &temperature(25.0), at(start, 10)));
+        assert!(state.should_report(&policy, &temperature(25.0), at(start, 60)));
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::mijiabt_data::{Quantity, ReadingSnapshot};
+
+#[derive(Debug, Clone)]
+/// A bounded history of the readings of a sensor.
+/// Once full, the oldest readings are dropped.
+pub struct SensorHistory {
+    /// The maximum number of readings kept.
+    capacity: usize,
+    /// The readings, from the oldest to the most recent.
+    snapshots: VecDeque<ReadingSnapshot>,
+}
+
+impl SensorHistory {
+    /// Create a new instance of SensorHistory.
+    ///
+    /// # Arguments:
+    /// * `capacity` - The maximum number of readings kept. The memory is allocated as the readings are added,
+    ///   so that usize::MAX stands for an unbounded history.
+    ///
+    /// Returns a new empty SensorHistory.
+    pub fn new(capacity: usize) -> SensorHistory {
+        SensorHistory {
+            capacity,
+            snapshots: VecDeque::new()
+        }
+    }
+
+    /// Add a reading to the history, dropping the oldest one if the history is full.
+    ///
+    /// # Arguments:
+    /// * `snapshot` - The reading.
+    pub fn push(&mut self, snapshot: ReadingSnapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Get the number of readings in the history.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Check whether the history is empty.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Get the most recent reading.
+    pub fn last(&self) -> Option<&ReadingSnapshot> {
+        self.snapshots.back()
+    }
+
+    /// Get the most recent readings.
+    ///
+    /// # Arguments:
+    /// * `n` - The maximum number of readings to get.
+    ///
+    /// Returns up to n readings, from the oldest to the most recent.
+    pub fn last_n(&self, n: usize) -> Vec<&ReadingSnapshot> {
+        self.snapshots.iter().skip(self.snapshots.len().saturating_sub(n)).collect()
+    }
+
+    /// Get the readings received within a time window.
+    ///
+    /// # Arguments:
+    /// * `window` - The time window, ending now. If None is passed, the whole history is returned.
+    ///
+    /// Returns the readings, from the oldest to the most recent.
+    pub fn within(&self, window: Option<Duration>) -> Vec<&ReadingSnapshot> {
+        self.snapshots.iter()
+            .filter(|snapshot| match window {
+                None => true,
+                Some(window) => snapshot.metadata().received_instant.elapsed() <= window
+            })
+            .collect()
+    }
+
+    /// Get the minimum value of a quantity within a time window.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The quantity.
+    /// * `window` - The time window, ending now. If None is passed, the whole history is used.
+    ///
+    /// Returns the minimum value, or None if no reading within the window contains the quantity.
+    pub fn min(&self, quantity: Quantity, window: Option<Duration>) -> Option<f32> {
+        self.values(quantity, window).fold(None, |min, value| match min {
+            Some(min) if min <= value => Some(min),
+            _ => Some(value)
+        })
+    }
+
+    /// Get the maximum value of a quantity within a time window.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The quantity.
+    /// * `window` - The time window, ending now. If None is passed, the whole history is used.
+    ///
+    /// Returns the maximum value, or None if no reading within the window contains the quantity.
+    pub fn max(&self, quantity: Quantity, window: Option<Duration>) -> Option<f32> {
+        self.values(quantity, window).fold(None, |max, value| match max {
+            Some(max) if max >= value => Some(max),
+            _ => Some(value)
+        })
+    }
+
+    /// Get the mean value of a quantity within a time window.
+    ///
+    /// # Arguments:
+    /// * `quantity` - The quantity.
+    /// * `window` - The time window, ending now. If None is passed, the whole history is used.
+    ///
+    /// Returns the mean value, or None if no reading within the window contains the quantity.
+    pub fn mean(&self, quantity: Quantity, window: Option<Duration>) -> Option<f32> {
+        let (sum, count) = self.values(quantity, window)
+            .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+
+        if count == 0 {
+            None
+        } else {
+            Some(sum / count as f32)
+        }
+    }
+
+    /// Get the calibrated values of a quantity within a time window.
+    fn values(&self, quantity: Quantity, window: Option<Duration>) -> impl Iterator<Item = f32> + '_ {
+        self.within(window).into_iter().filter_map(move |snapshot| quantity.value(snapshot.reading()))
+    }
+}
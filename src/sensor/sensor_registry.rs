@@ -0,0 +1,102 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Serialize, Deserialize};
+
+use crate::sensor::address::normalize_address;
+use crate::sensor::Calibration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The user provided information about a sensor.
+pub struct SensorInfo {
+    /// The display name of the sensor.
+    pub name: String,
+    /// The room the sensor is located in, if any.
+    pub room: Option<String>,
+    /// Arbitrary key-value tags attached to the sensor.
+    pub tags: BTreeMap<String, String>,
+}
+
+impl SensorInfo {
+    /// Create a new instance of SensorInfo.
+    ///
+    /// # Arguments:
+    /// * `name` - The display name of the sensor.
+    ///
+    /// Returns a new SensorInfo with no room and no tags.
+    pub fn new(name: &str) -> SensorInfo {
+        SensorInfo {
+            name: String::from(name),
+            room: None,
+            tags: BTreeMap::new()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A registry mapping the MAC address of the sensors to their information and calibration.
+pub struct SensorRegistry {
+    /// The information about the sensors, by normalized MAC address.
+    sensors: HashMap<String, SensorInfo>,
+    /// The calibration of the sensors, by normalized MAC address.
+    calibrations: HashMap<String, Calibration>,
+}
+
+impl SensorRegistry {
+    /// Create a new instance of SensorRegistry.
+    ///
+    /// Returns a new empty SensorRegistry.
+    pub fn new() -> SensorRegistry {
+        SensorRegistry {
+            sensors: HashMap::new(),
+            calibrations: HashMap::new()
+        }
+    }
+
+    /// Register a sensor, replacing any previous information about it.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    /// * `info` - The information about the sensor.
+    pub fn register(&mut self, address: &str, info: SensorInfo) {
+        self.sensors.insert(normalize_address(address), info);
+    }
+
+    /// Unregister a sensor.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    ///
+    /// Returns the information about the sensor, if it was registered.
+    pub fn unregister(&mut self, address: &str) -> Option<SensorInfo> {
+        self.sensors.remove(&normalize_address(address))
+    }
+
+    /// Get the information about a sensor.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    ///
+    /// Returns the information about the sensor, if it is registered.
+    pub fn get(&self, address: &str) -> Option<&SensorInfo> {
+        self.sensors.get(&normalize_address(address))
+    }
+
+    /// Set the calibration of a sensor, replacing any previous one.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    /// * `calibration` - The calibration of the sensor.
+    pub fn set_calibration(&mut self, address: &str, calibration: Calibration) {
+        self.calibrations.insert(normalize_address(address), calibration);
+    }
+
+    /// Get the calibration of a sensor.
+    ///
+    /// # Arguments:
+    /// * `address` - The MAC address of the sensor.
+    ///
+    /// Returns the calibration of the sensor, leaving the values unchanged if none was set.
+    pub fn calibration(&self, address: &str) -> Calibration {
+        self.calibrations.get(&normalize_address(address)).copied().unwrap_or_default()
+    }
+}
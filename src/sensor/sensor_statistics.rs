@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::sensor::LinkQuality;
+
+/// The time window the advertisement rate is measured over.
+const ADVERTISEMENT_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+/// The reception statistics of a sensor.
+pub struct SensorStatistics {
+    /// The monotonic time the last advertisement has been received at.
+    last_seen: Instant,
+    /// The wall clock time the last advertisement has been received at.
+    last_seen_at: SystemTime,
+    /// The reception times of the advertisements within the rate window.
+    recent_advertisements: VecDeque<Instant>,
+    /// The number of advertisements received, duplicates included.
+    advertisements: u64,
+    /// The number of frames which have been decoded.
+    decoded_frames: u64,
+    /// The number of frames which could not be decoded.
+    undecodable_frames: u64,
+    /// The minimum received signal strength, in dBm.
+    rssi_min: Option<i16>,
+    /// The maximum received signal strength, in dBm.
+    rssi_max: Option<i16>,
+    /// The sum of the received signal strengths, in dBm.
+    rssi_sum: i64,
+    /// The number of received signal strengths.
+    rssi_count: u64,
+    /// The link quality, measured from the frame counters.
+    link_quality: LinkQuality,
+}
+
+impl SensorStatistics {
+    /// Create a new instance of SensorStatistics.
+    ///
+    /// Returns a new SensorStatistics, with nothing received yet.
+    pub(crate) fn new() -> SensorStatistics {
+        SensorStatistics {
+            last_seen: Instant::now(),
+            last_seen_at: SystemTime::now(),
+            recent_advertisements: VecDeque::new(),
+            advertisements: 0,
+            decoded_frames: 0,
+            undecodable_frames: 0,
+            rssi_min: None,
+            rssi_max: None,
+            rssi_sum: 0,
+            rssi_count: 0,
+            link_quality: LinkQuality::default()
+        }
+    }
+
+    /// Record a received advertisement.
+    ///
+    /// # Arguments:
+    /// * `rssi` - The received signal strength of the advertisement, if any.
+    pub(crate) fn record_advertisement(&mut self, rssi: Option<i16>) {
+        let now = Instant::now();
+
+        self.last_seen = now;
+        self.last_seen_at = SystemTime::now();
+        self.advertisements += 1;
+
+        self.recent_advertisements.push_back(now);
+        self.forget_old_advertisements(now);
+
+        if let Some(rssi) = rssi {
+            self.rssi_min = Some(self.rssi_min.map_or(rssi, |rssi_min| rssi_min.min(rssi)));
+            self.rssi_max = Some(self.rssi_max.map_or(rssi, |rssi_max| rssi_max.max(rssi)));
+            self.rssi_sum += i64::from(rssi);
+            self.rssi_count += 1;
+        }
+    }
+
+    /// Record a frame, once the duplicates have been dropped.
+    ///
+    /// # Arguments:
+    /// * `decoded` - Whether the frame has been decoded or not.
+    pub(crate) fn record_frame(&mut self, decoded: bool) {
+        if decoded {
+            self.decoded_frames += 1;
+        } else {
+            self.undecodable_frames += 1;
+        }
+    }
+
+    /// Set the link quality measured from the frame counters.
+    pub(crate) fn set_link_quality(&mut self, link_quality: LinkQuality) {
+        self.link_quality = link_quality;
+    }
+
+    /// Get the monotonic time the last advertisement has been received at.
+    pub fn last_seen(&self) -> Instant {
+        self.last_seen
+    }
+
+    /// Get the wall clock time the last advertisement has been received at.
+    pub fn last_seen_at(&self) -> SystemTime {
+        self.last_seen_at
+    }
+
+    /// Get the number of advertisements received during the last minute, duplicates included.
+    pub fn advertisements_per_minute(&self) -> usize {
+        self.recent_advertisements.iter()
+            .filter(|received| received.elapsed() <= ADVERTISEMENT_RATE_WINDOW)
+            .count()
+    }
+
+    /// Get the number of advertisements received, duplicates included.
+    pub fn advertisements(&self) -> u64 {
+        self.advertisements
+    }
+
+    /// Get the number of frames which have been decoded.
+    pub fn decoded_frames(&self) -> u64 {
+        self.decoded_frames
+    }
+
+    /// Get the number of frames which could not be decoded.
+    pub fn undecodable_frames(&self) -> u64 {
+        self.undecodable_frames
+    }
+
+    /// Get the minimum received signal strength, in dBm.
+    pub fn rssi_min(&self) -> Option<i16> {
+        self.rssi_min
+    }
+
+    /// Get the maximum received signal strength, in dBm.
+    pub fn rssi_max(&self) -> Option<i16> {
+        self.rssi_max
+    }
+
+    /// Get the mean received signal strength, in dBm.
+    pub fn rssi_mean(&self) -> Option<f32> {
+        if self.rssi_count == 0 {
+            None
+        } else {
+            Some(self.rssi_sum as f32 / self.rssi_count as f32)
+        }
+    }
+
+    /// Get the link quality, measured from the frame counters.
+    pub fn link_quality(&self) -> LinkQuality {
+        self.link_quality
+    }
+
+    /// Forget the advertisements received before the rate window.
+    fn forget_old_advertisements(&mut self, now: Instant) {
+        while let Some(received) = self.recent_advertisements.front() {
+            if now.saturating_duration_since(*received) <= ADVERTISEMENT_RATE_WINDOW {
+                break;
+            }
+
+            self.recent_advertisements.pop_front();
+        }
+    }
+}